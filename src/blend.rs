@@ -0,0 +1,268 @@
+// "asi_vulkan" - Aldaron's System Interface - Vulkan
+//
+// Copyright Jeron A. Lau 2018.
+// Distributed under the Boost Software License, Version 1.0.
+// (See accompanying file LICENSE_1_0.txt or copy at
+// https://www.boost.org/LICENSE_1_0.txt)
+
+use types::*;
+use std::ops::BitOr;
+
+/// What the source (new) or destination (already drawn) color or alpha is
+/// multiplied by before blending.
+#[derive(Copy, Clone, Debug, PartialEq)] pub enum BlendFactor {
+	Zero,
+	One,
+	SrcColor,
+	OneMinusSrcColor,
+	DstColor,
+	OneMinusDstColor,
+	SrcAlpha,
+	OneMinusSrcAlpha,
+	DstAlpha,
+	OneMinusDstAlpha,
+	/// The blend constant color.
+	ConstantColor,
+	OneMinusConstantColor,
+	/// The blend constant alpha.
+	ConstantAlpha,
+	OneMinusConstantAlpha,
+	SrcAlphaSaturate,
+	/// The second output of the fragment shader (needs dual-source
+	/// blending support).
+	Src1Color,
+	OneMinusSrc1Color,
+	Src1Alpha,
+	OneMinusSrc1Alpha,
+}
+
+impl BlendFactor {
+	/// Whether or not this factor needs dual-source blending.
+	pub(crate) fn dual_source(&self) -> bool {
+		matches!(*self, BlendFactor::Src1Color
+			| BlendFactor::OneMinusSrc1Color | BlendFactor::Src1Alpha
+			| BlendFactor::OneMinusSrc1Alpha)
+	}
+
+	pub(crate) fn vk(&self) -> VkBlendFactor {
+		use self::BlendFactor::*;
+
+		match *self {
+			Zero => VkBlendFactor::Zero,
+			One => VkBlendFactor::One,
+			SrcColor => VkBlendFactor::SrcColor,
+			OneMinusSrcColor => VkBlendFactor::OneMinusSrcColor,
+			DstColor => VkBlendFactor::DstColor,
+			OneMinusDstColor => VkBlendFactor::OneMinusDstColor,
+			SrcAlpha => VkBlendFactor::SrcAlpha,
+			OneMinusSrcAlpha => VkBlendFactor::OneMinusSrcAlpha,
+			DstAlpha => VkBlendFactor::DstAlpha,
+			OneMinusDstAlpha => VkBlendFactor::OneMinusDstAlpha,
+			ConstantColor => VkBlendFactor::ConstantColor,
+			OneMinusConstantColor =>
+				VkBlendFactor::OneMinusConstantColor,
+			ConstantAlpha => VkBlendFactor::ConstantAlpha,
+			OneMinusConstantAlpha =>
+				VkBlendFactor::OneMinusConstantAlpha,
+			SrcAlphaSaturate => VkBlendFactor::SrcAlphaSaturate,
+			Src1Color => VkBlendFactor::Src1Color,
+			OneMinusSrc1Color => VkBlendFactor::OneMinusSrc1Color,
+			Src1Alpha => VkBlendFactor::Src1Alpha,
+			OneMinusSrc1Alpha => VkBlendFactor::OneMinusSrc1Alpha,
+		}
+	}
+}
+
+/// How the multiplied source and destination are combined.
+#[derive(Copy, Clone, Debug, PartialEq)] pub enum BlendOp {
+	/// Source + destination.
+	Add,
+	/// Source - destination.
+	Subtract,
+	/// Destination - source.
+	ReverseSubtract,
+	/// The smaller of source and destination (factors are ignored).
+	Min,
+	/// The larger of source and destination (factors are ignored).
+	Max,
+}
+
+impl BlendOp {
+	pub(crate) fn vk(&self) -> VkBlendOp {
+		match *self {
+			BlendOp::Add => VkBlendOp::Add,
+			BlendOp::Subtract => VkBlendOp::Subtract,
+			BlendOp::ReverseSubtract => VkBlendOp::ReverseSubtract,
+			BlendOp::Min => VkBlendOp::Min,
+			BlendOp::Max => VkBlendOp::Max,
+		}
+	}
+}
+
+/// A blend equation, for color and alpha separately.
+#[derive(Copy, Clone, Debug, PartialEq)] pub struct Blend {
+	pub src_color: BlendFactor,
+	pub dst_color: BlendFactor,
+	pub color_op: BlendOp,
+	pub src_alpha: BlendFactor,
+	pub dst_alpha: BlendFactor,
+	pub alpha_op: BlendOp,
+}
+
+impl Blend {
+	/// Straight (not premultiplied) alpha blending, as with
+	/// `StyleBuilder::alpha()`.
+	pub const ALPHA: Blend = Blend {
+		src_color: BlendFactor::SrcAlpha,
+		dst_color: BlendFactor::OneMinusSrcAlpha,
+		color_op: BlendOp::Add,
+		src_alpha: BlendFactor::SrcAlpha,
+		dst_alpha: BlendFactor::One,
+		alpha_op: BlendOp::Add,
+	};
+
+	/// Alpha blending of colors already multiplied by their alpha.
+	pub const PREMULTIPLIED: Blend = Blend {
+		src_color: BlendFactor::One,
+		dst_color: BlendFactor::OneMinusSrcAlpha,
+		color_op: BlendOp::Add,
+		src_alpha: BlendFactor::One,
+		dst_alpha: BlendFactor::OneMinusSrcAlpha,
+		alpha_op: BlendOp::Add,
+	};
+
+	/// Add to what's drawn, for lights and particles.
+	pub const ADDITIVE: Blend = Blend {
+		src_color: BlendFactor::One,
+		dst_color: BlendFactor::One,
+		color_op: BlendOp::Add,
+		src_alpha: BlendFactor::One,
+		dst_alpha: BlendFactor::One,
+		alpha_op: BlendOp::Add,
+	};
+
+	/// Multiply what's drawn (darkens), keeping its alpha.
+	pub const MULTIPLY: Blend = Blend {
+		src_color: BlendFactor::DstColor,
+		dst_color: BlendFactor::Zero,
+		color_op: BlendOp::Add,
+		src_alpha: BlendFactor::Zero,
+		dst_alpha: BlendFactor::One,
+		alpha_op: BlendOp::Add,
+	};
+
+	/// Inverse multiply of the inverses (lightens), keeping the alpha of
+	/// what's drawn.
+	pub const SCREEN: Blend = Blend {
+		src_color: BlendFactor::One,
+		dst_color: BlendFactor::OneMinusSrcColor,
+		color_op: BlendOp::Add,
+		src_alpha: BlendFactor::Zero,
+		dst_alpha: BlendFactor::One,
+		alpha_op: BlendOp::Add,
+	};
+
+	/// Whether or not this equation needs dual-source blending.
+	pub(crate) fn dual_source(&self) -> bool {
+		self.src_color.dual_source() || self.dst_color.dual_source()
+			|| self.src_alpha.dual_source()
+			|| self.dst_alpha.dual_source()
+	}
+}
+
+/// Which channels of a color attachment are written, combined with `|`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)] pub struct ColorMask(pub(crate) u32);
+
+impl ColorMask {
+	pub const R: ColorMask = ColorMask(0b0001);
+	pub const G: ColorMask = ColorMask(0b0010);
+	pub const B: ColorMask = ColorMask(0b0100);
+	pub const A: ColorMask = ColorMask(0b1000);
+	pub const RGB: ColorMask = ColorMask(0b0111);
+	pub const RGBA: ColorMask = ColorMask(0b1111);
+	pub const NONE: ColorMask = ColorMask(0);
+}
+
+impl BitOr for ColorMask {
+	type Output = ColorMask;
+
+	fn bitor(self, other: ColorMask) -> ColorMask {
+		ColorMask(self.0 | other.0)
+	}
+}
+
+/// How to draw into one color attachment.
+#[derive(Copy, Clone, Debug, PartialEq)] pub struct Attachment {
+	/// The blend equation, or `None` to replace what's drawn.
+	pub blend: Option<Blend>,
+	/// Which channels to write.
+	pub write_mask: ColorMask,
+}
+
+impl Attachment {
+	/// Write all channels, blending with `blend` if it's not `None`.
+	pub fn new(blend: Option<Blend>) -> Self {
+		Attachment { blend, write_mask: ColorMask::RGBA }
+	}
+
+	pub(crate) fn vk(&self) -> VkPipelineColorBlendAttachmentState {
+		let blend = self.blend.unwrap_or(Blend::ALPHA);
+
+		VkPipelineColorBlendAttachmentState {
+			blend_enable: self.blend.is_some() as VkBool32,
+			src_color_blend_factor: blend.src_color.vk(),
+			dst_color_blend_factor: blend.dst_color.vk(),
+			color_blend_op: blend.color_op.vk(),
+			src_alpha_blend_factor: blend.src_alpha.vk(),
+			dst_alpha_blend_factor: blend.dst_alpha.vk(),
+			alpha_blend_op: blend.alpha_op.vk(),
+			color_write_mask: self.write_mask.0,
+		}
+	}
+}
+
+/// A bitwise operation between the source and destination, replacing
+/// blending for all attachments.
+#[derive(Copy, Clone, Debug, PartialEq)] pub enum LogicOp {
+	Clear,
+	And,
+	AndReverse,
+	Copy,
+	AndInverted,
+	NoOp,
+	Xor,
+	Or,
+	Nor,
+	Equivalent,
+	Invert,
+	OrReverse,
+	CopyInverted,
+	OrInverted,
+	Nand,
+	Set,
+}
+
+impl LogicOp {
+	pub(crate) fn vk(&self) -> VkLogicOp {
+		use self::LogicOp::*;
+
+		match *self {
+			Clear => VkLogicOp::Clear,
+			And => VkLogicOp::And,
+			AndReverse => VkLogicOp::AndReverse,
+			Copy => VkLogicOp::Copy,
+			AndInverted => VkLogicOp::AndInverted,
+			NoOp => VkLogicOp::NoOp,
+			Xor => VkLogicOp::Xor,
+			Or => VkLogicOp::Or,
+			Nor => VkLogicOp::Nor,
+			Equivalent => VkLogicOp::Equivalent,
+			Invert => VkLogicOp::Invert,
+			OrReverse => VkLogicOp::OrReverse,
+			CopyInverted => VkLogicOp::CopyInverted,
+			OrInverted => VkLogicOp::OrInverted,
+			Nand => VkLogicOp::Nand,
+			Set => VkLogicOp::Set,
+		}
+	}
+}
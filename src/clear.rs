@@ -0,0 +1,38 @@
+// "asi_vulkan" - Aldaron's System Interface - Vulkan
+//
+// Copyright Jeron A. Lau 2018.
+// Distributed under the Boost Software License, Version 1.0.
+// (See accompanying file LICENSE_1_0.txt or copy at
+// https://www.boost.org/LICENSE_1_0.txt)
+
+use types::*;
+
+/// What an attachment starts out as when a render pass begins, or what part
+/// of it is cleared to with `cmd_clear_attachments()`.
+#[derive(Copy, Clone, Debug, PartialEq)] pub enum Clear {
+	/// Clear a color attachment to RGBA.
+	Color([f32; 4]),
+	/// Clear the depth attachment to a depth and stencil value.
+	DepthStencil(f32, u32),
+	/// Don't clear, load the previous contents.
+	Load,
+}
+
+impl Clear {
+	pub(crate) fn value(&self) -> VkClearValue {
+		match *self {
+			Clear::Color(rgba) => VkClearValue {
+				color: VkClearColorValue { float32: rgba }
+			},
+			Clear::DepthStencil(depth, stencil) => VkClearValue {
+				depth_stencil: VkClearDepthStencilValue {
+					depth, stencil
+				}
+			},
+			// Ignored by Vulkan.
+			Clear::Load => VkClearValue {
+				color: VkClearColorValue { float32: [0.0; 4] }
+			},
+		}
+	}
+}
@@ -0,0 +1,1253 @@
+// "asi_vulkan" - Aldaron's System Interface - Vulkan
+//
+// Copyright Jeron A. Lau 2018.
+// Distributed under the Boost Software License, Version 1.0.
+// (See accompanying file LICENSE_1_0.txt or copy at
+// https://www.boost.org/LICENSE_1_0.txt)
+
+use null;
+use null_mut;
+use mem;
+
+use Gpu;
+use Clear;
+use Fence;
+use Image;
+use Style;
+use Stages;
+use Barrier;
+use QueryPool;
+use IndexType;
+use DrawIndirectCommand;
+use DrawIndexedIndirectCommand;
+use RenderTarget;
+use Viewport;
+use Scissor;
+use types::*;
+use c_void;
+use std::{ rc::Rc, sync::Arc, cell::Cell };
+
+/// A `VkCommandPool`, which `CommandBuffer`s are allocated from.
+#[derive(Clone)] pub struct CommandPool(Rc<CommandPoolContext>);
+
+struct CommandPoolContext {
+	pool: u64,
+	vulkan: Gpu,
+}
+
+impl CommandPool {
+	/// Create a command pool for the queue that `Gpu` submits to.
+	pub fn new(vulkan: &Gpu) -> Self { unsafe {
+		let connection = vulkan.get();
+		let mut pool = 0;
+
+		(connection.new_cmdpool)(
+			connection.device,
+			&VkCommandPoolCreateInfo {
+				s_type: VkStructureType::CommandPoolCreateInfo,
+				p_next: null_mut(),
+				flags: 0x00000002, // Reset Command Buffer
+				queue_family_index: connection.queue_family,
+			},
+			null(),
+			&mut pool
+		).unwrap();
+
+		CommandPool(Rc::new(CommandPoolContext {
+			pool, vulkan: vulkan.clone()
+		}))
+	} }
+
+	/// Allocate a new primary command buffer from this pool.
+	pub fn command_buffer(&self) -> CommandBuffer { unsafe {
+		let connection = self.0.vulkan.get();
+		let mut buffer = null_mut();
+
+		(connection.new_cmdbuffs)(
+			connection.device,
+			&VkCommandBufferAllocateInfo {
+				s_type: VkStructureType::CommandBufferAllocateInfo,
+				p_next: null_mut(),
+				command_pool: self.0.pool,
+				level: VkCommandBufferLevel::Primary,
+				command_buffer_count: 1,
+			},
+			&mut buffer
+		).unwrap();
+
+		CommandBuffer {
+			buffer, pool: self.clone(), executable: Cell::new(false),
+		}
+	} }
+}
+
+impl Drop for CommandPoolContext {
+	fn drop(&mut self) {
+		let vk = self.vulkan.get();
+
+		unsafe {
+			(vk.drop_cmdpool)(vk.device, self.pool, null());
+		}
+	}
+}
+
+/// A `VkCommandBuffer`, recorded with `begin()` and then submitted to the
+/// queue independently of any other command buffer.
+pub struct CommandBuffer {
+	buffer: VkCommandBuffer,
+	pool: CommandPool,
+	// Whether recording has ended, and it hasn't been submitted since.
+	executable: Cell<bool>,
+}
+
+impl CommandBuffer {
+	/// Start recording commands, replacing any previously recorded.
+	///
+	/// # Safety
+	/// This command buffer must not still be executing (wait on the `Fence`
+	/// it was submitted with first).
+	pub unsafe fn begin(&mut self) -> Recorder<'_> {
+		{
+			let connection = self.pool.0.vulkan.get();
+
+			::begin_cmdbuff(&connection, self.buffer);
+		}
+
+		self.executable.set(false);
+
+		Recorder { cmdbuf: self }
+	}
+
+	/// Submit the commands recorded since `begin()` to the queue, which
+	/// signals `fence` (and `semaphore`, if there is one) when finished.
+	/// They may only be submitted once.
+	///
+	/// # Safety
+	/// Everything the commands use must live until `fence` is signaled.
+	pub unsafe fn submit(&self, fence: &Fence, stage: VkPipelineStage,
+		semaphore: Option<VkSemaphore>)
+	{
+		assert!(self.executable.get(),
+			"Command buffer must be recorded before submitting");
+
+		let connection = self.pool.0.vulkan.get();
+
+		::submit(&connection, self.buffer, fence, stage, semaphore);
+
+		self.executable.set(false);
+	}
+
+	/// Get the `VkCommandBuffer`.
+	pub fn buffer(&self) -> VkCommandBuffer {
+		self.buffer
+	}
+}
+
+impl Drop for CommandBuffer {
+	fn drop(&mut self) {
+		let vk = self.pool.0.vulkan.get();
+
+		unsafe {
+			(vk.drop_cmdbuffs)(vk.device, self.pool.0.pool, 1,
+				&self.buffer);
+		}
+	}
+}
+
+/// Records commands outside of a render pass.  Call `end()` when finished.
+pub struct Recorder<'a> {
+	cmdbuf: &'a mut CommandBuffer,
+}
+
+impl<'a> Recorder<'a> {
+	/// Begin drawing to the swapchain `image` (transitioning it from being
+	/// presented), with the color and depth attachments cleared as in
+	/// `draw_begin_clear()`.
+	///
+	/// # Safety
+	/// `image` and `frame_buffer` must be from the swapchain that `render_pass`
+	/// was created for.
+	pub unsafe fn begin_swapchain(self, render_pass: VkRenderPass,
+		image: VkImage, frame_buffer: VkFramebuffer, clear: &[Clear])
+		-> RenderPassRecorder<'a>
+	{
+		{
+			let connection = self.vulkan().get();
+
+			::begin_swapchain(&connection, self.cmdbuf.buffer,
+				render_pass, image, frame_buffer, clear,
+				VkSubpassContents::Inline);
+		}
+
+		RenderPassRecorder { cmdbuf: self.cmdbuf }
+	}
+
+	/// Like `begin_swapchain()`, but the render pass is drawn by executing
+	/// `SecondaryCommandBuffer`s.
+	///
+	/// # Safety
+	/// `image` and `frame_buffer` must be from the swapchain that
+	/// `render_pass` was created for, and the secondary command buffers
+	/// executed must have been begun with `render_pass` (or a compatible
+	/// one).
+	pub unsafe fn begin_swapchain_secondary(self, render_pass: VkRenderPass,
+		image: VkImage, frame_buffer: VkFramebuffer, clear: &[Clear])
+		-> SecondaryRenderPass<'a>
+	{
+		{
+			let connection = self.vulkan().get();
+
+			::begin_swapchain(&connection, self.cmdbuf.buffer,
+				render_pass, image, frame_buffer, clear,
+				VkSubpassContents::SecondaryCommandBuffers);
+		}
+
+		SecondaryRenderPass { cmdbuf: self.cmdbuf }
+	}
+
+	/// Begin drawing to an offscreen `RenderTarget`, with the attachments
+	/// loaded or cleared as in `draw_begin_target_clear()`.
+	///
+	/// # Safety
+	/// `target` must live until the command buffer has finished executing.
+	pub unsafe fn begin_target(self, target: &RenderTarget,
+		clear: &[Clear]) -> RenderPassRecorder<'a>
+	{
+		{
+			let connection = self.vulkan().get();
+
+			::begin_target(&connection, self.cmdbuf.buffer, target,
+				clear, VkSubpassContents::Inline);
+		}
+
+		RenderPassRecorder { cmdbuf: self.cmdbuf }
+	}
+
+	/// Like `begin_target()`, but the render pass is drawn by executing
+	/// `SecondaryCommandBuffer`s.
+	///
+	/// # Safety
+	/// `target` must live until the command buffer has finished executing,
+	/// and the secondary command buffers executed must have been begun with
+	/// its render pass and framebuffer.
+	pub unsafe fn begin_target_secondary(self, target: &RenderTarget,
+		clear: &[Clear]) -> SecondaryRenderPass<'a>
+	{
+		{
+			let connection = self.vulkan().get();
+
+			::begin_target(&connection, self.cmdbuf.buffer, target,
+				clear, VkSubpassContents::SecondaryCommandBuffers);
+		}
+
+		SecondaryRenderPass { cmdbuf: self.cmdbuf }
+	}
+
+	/// Bind a compute pipeline for the following dispatches.
+	///
+	/// # Safety
+	/// `pipeline` must be a compute pipeline that lives until the command
+	/// buffer has finished executing.
+	pub unsafe fn bind_compute(&mut self, pipeline: VkPipeline) {
+		let connection = self.vulkan().get();
+
+		(connection.bind_pipeline)(self.cmdbuf.buffer,
+			VkPipelineBindPoint::Compute, pipeline);
+	}
+
+	/// Bind a descriptor set for the following dispatches.
+	///
+	/// # Safety
+	/// `desc_set` must match set 0 of `pipeline_layout`, and must not be
+	/// updated or dropped until the command buffer has finished executing.
+	pub unsafe fn bind_compute_descsets(&mut self,
+		pipeline_layout: VkPipelineLayout, desc_set: VkDescriptorSet)
+	{
+		let connection = self.vulkan().get();
+
+		(connection.bind_descsets)(self.cmdbuf.buffer,
+			VkPipelineBindPoint::Compute, pipeline_layout, 0, 1,
+			[desc_set].as_ptr(), 0, null());
+	}
+
+	/// Set push constants as in `cmd_push_constants()`.
+	///
+	/// # Safety
+	/// `T` must not have padding, and `offset` plus its size must be within
+	/// a range of `pipeline_layout` for `stages`, which must be the
+	/// layout of the bound compute pipeline (or compatible with it).
+	pub unsafe fn push_constants<T: Copy>(&mut self,
+		pipeline_layout: VkPipelineLayout, stages: Stages, offset: u32,
+		data: &T)
+	{
+		let connection = self.vulkan().get();
+
+		::push_constants(&connection, self.cmdbuf.buffer,
+			pipeline_layout, stages, offset, data);
+	}
+
+	/// Run the bound compute pipeline for `x` by `y` by `z` work groups.
+	///
+	/// # Safety
+	/// A compute pipeline and the descriptor sets it reads must be bound.
+	pub unsafe fn dispatch(&mut self, x: u32, y: u32, z: u32) {
+		let connection = self.vulkan().get();
+
+		::dispatch(&connection, self.cmdbuf.buffer, x, y, z);
+	}
+
+	/// Dispatch as in `cmd_dispatch_indirect()`.
+	///
+	/// # Safety
+	/// A compute pipeline and what it reads must be bound, `offset` must be
+	/// a multiple of 4 with the counts within `buffer`, and the counts must
+	/// be within the GPU's limits.  `buffer` must live until the command
+	/// buffer has finished executing.
+	pub unsafe fn dispatch_indirect(&mut self, buffer: VkBuffer,
+		offset: u64)
+	{
+		let connection = self.vulkan().get();
+
+		(connection.dispatch_indirect)(self.cmdbuf.buffer, buffer,
+			offset);
+	}
+
+	/// Make earlier writes visible to later commands, as in
+	/// `cmd_barrier()`.
+	pub fn barrier(&mut self, barrier: Barrier) { unsafe {
+		let connection = self.vulkan().get();
+
+		::memory_barrier(&connection, self.cmdbuf.buffer, barrier);
+	} }
+
+	/// Reset queries, as in `cmd_reset_queries()`.
+	///
+	/// # Safety
+	/// The queries must not be in use by a command buffer that hasn't
+	/// finished, and `pool` must live until the command buffer has finished
+	/// executing.
+	pub unsafe fn reset_queries(&mut self, pool: &QueryPool, first: u32,
+		count: u32)
+	{
+		let connection = self.vulkan().get();
+
+		::reset_queries(&connection, self.cmdbuf.buffer, pool, first,
+			count);
+	}
+
+	/// Write a timestamp, as in `cmd_write_timestamp()`.
+	///
+	/// # Safety
+	/// `query` must have been reset since it was last written, and `pool`
+	/// must live until the command buffer has finished executing.
+	pub unsafe fn write_timestamp(&mut self, pool: &QueryPool, query: u32,
+		stage: VkPipelineStage)
+	{
+		let connection = self.vulkan().get();
+
+		::write_timestamp(&connection, self.cmdbuf.buffer, pool, query,
+			stage);
+	}
+
+	/// Transition the swapchain `image` to be presented, after drawing.
+	///
+	/// # Safety
+	/// `image` must be the swapchain image that was drawn to.
+	pub unsafe fn present_barrier(&mut self, image: VkImage) {
+		let connection = self.vulkan().get();
+
+		::present_barrier(&connection, self.cmdbuf.buffer, image);
+	}
+
+	/// Copy `width` by `height` pixels from one image to another.
+	///
+	/// # Safety
+	/// Both images must be at least `width` by `height`, and must live until
+	/// the command buffer has finished executing.
+	pub unsafe fn copy_image(&mut self, src_image: &Image,
+		dst_image: &Image, width: u16, height: u16)
+	{
+		let connection = self.vulkan().get();
+
+		::copy(&connection, self.cmdbuf.buffer, src_image, dst_image,
+			width, height);
+	}
+
+	/// Finish recording, so the command buffer can be submitted.
+	///
+	/// # Safety
+	/// The commands recorded must be valid usage of Vulkan, which isn't all
+	/// checked.
+	pub unsafe fn end(self) {
+		{
+			let connection = self.vulkan().get();
+
+			(connection.end_cmdbuff)(self.cmdbuf.buffer).unwrap();
+		}
+
+		self.cmdbuf.executable.set(true);
+	}
+
+	fn vulkan(&self) -> &Gpu {
+		&self.cmdbuf.pool.0.vulkan
+	}
+}
+
+/// Records commands inside of a render pass.  Call `end_render_pass()` when
+/// finished.
+pub struct RenderPassRecorder<'a> {
+	cmdbuf: &'a mut CommandBuffer,
+}
+
+impl<'a> RenderPassRecorder<'a> {
+	/// Bind a graphics pipeline for the following draws.
+	///
+	/// # Safety
+	/// `pipeline` must be a graphics pipeline for a compatible render pass,
+	/// that lives until the command buffer has finished executing.
+	pub unsafe fn bind_pipeline(&mut self, pipeline: VkPipeline) {
+		let connection = self.vulkan().get();
+
+		(connection.bind_pipeline)(self.cmdbuf.buffer,
+			VkPipelineBindPoint::Graphics, pipeline);
+	}
+
+	/// Bind a descriptor set for the following draws.
+	///
+	/// # Safety
+	/// `desc_set` must match set 0 of `pipeline_layout`, and must not be
+	/// updated or dropped until the command buffer has finished executing.
+	pub unsafe fn bind_descsets(&mut self,
+		pipeline_layout: VkPipelineLayout, desc_set: VkDescriptorSet)
+	{
+		let connection = self.vulkan().get();
+
+		(connection.bind_descsets)(self.cmdbuf.buffer,
+			VkPipelineBindPoint::Graphics, pipeline_layout, 0, 1,
+			[desc_set].as_ptr(), 0, null());
+	}
+
+	/// Bind descriptor set `first_set`, as in `cmd_bind_descsets_at()`.
+	///
+	/// # Safety
+	/// `desc_set` must match set `first_set` of `pipeline_layout`, and must
+	/// not be updated or dropped until the command buffer has finished
+	/// executing.
+	pub unsafe fn bind_descsets_at(&mut self,
+		pipeline_layout: VkPipelineLayout, first_set: u32,
+		desc_set: VkDescriptorSet)
+	{
+		let connection = self.vulkan().get();
+
+		(connection.bind_descsets)(self.cmdbuf.buffer,
+			VkPipelineBindPoint::Graphics, pipeline_layout,
+			first_set, 1, [desc_set].as_ptr(), 0, null());
+	}
+
+	/// Bind vertex buffers for the following draws.
+	///
+	/// # Safety
+	/// The buffers must match the bound pipeline's vertex bindings, and live
+	/// until the command buffer has finished executing.
+	pub unsafe fn bind_vb(&mut self, vertex_buffers: &[VkBuffer]) {
+		let connection = self.vulkan().get();
+
+		::bind_vb(&connection, self.cmdbuf.buffer, 0, vertex_buffers,
+			&vec![0; vertex_buffers.len()]);
+	}
+
+	/// Bind vertex buffers as in `cmd_bind_vb_offsets()`.
+	///
+	/// # Safety
+	/// The buffers must match the bound pipeline's vertex bindings from
+	/// `first`, each offset must be within its buffer, and the buffers must
+	/// live until the command buffer has finished executing.
+	pub unsafe fn bind_vb_offsets(&mut self, first: u32,
+		vertex_buffers: &[VkBuffer], offsets: &[u64])
+	{
+		let connection = self.vulkan().get();
+
+		::bind_vb(&connection, self.cmdbuf.buffer, first, vertex_buffers,
+			offsets);
+	}
+
+	/// Write a timestamp, as in `cmd_write_timestamp()`.
+	///
+	/// # Safety
+	/// `query` must have been reset since it was last written, and `pool`
+	/// must live until the command buffer has finished executing.
+	pub unsafe fn write_timestamp(&mut self, pool: &QueryPool, query: u32,
+		stage: VkPipelineStage)
+	{
+		let connection = self.vulkan().get();
+
+		::write_timestamp(&connection, self.cmdbuf.buffer, pool, query,
+			stage);
+	}
+
+	/// Set the blend constants, as in `cmd_set_blend_constants()`.
+	pub fn set_blend_constants(&mut self, rgba: [f32; 4]) { unsafe {
+		let connection = self.vulkan().get();
+
+		(connection.set_blend_constants)(self.cmdbuf.buffer, &rgba);
+	} }
+
+	/// Start a query, as in `cmd_begin_query()`.
+	///
+	/// # Safety
+	/// `query` must have been reset since it was last used, no other query
+	/// of its kind may be active, and `pool` must live until the command
+	/// buffer has finished executing.
+	pub unsafe fn begin_query(&mut self, pool: &QueryPool, query: u32,
+		precise: bool)
+	{
+		let connection = self.vulkan().get();
+
+		::begin_query(&connection, self.cmdbuf.buffer, pool, query,
+			precise);
+	}
+
+	/// Stop a query started with `begin_query()`.
+	///
+	/// # Safety
+	/// `query` must have been started with `begin_query()` inside of this
+	/// render pass.
+	pub unsafe fn end_query(&mut self, pool: &QueryPool, query: u32) {
+		let connection = self.vulkan().get();
+
+		::end_query(&connection, self.cmdbuf.buffer, pool, query);
+	}
+
+	/// Draw `nvertices` vertices, `ninstances` times.
+	///
+	/// # Safety
+	/// A pipeline and what it reads must be bound, and the vertices must be
+	/// within the bound vertex buffers.
+	pub unsafe fn draw(&mut self, nvertices: u32, ninstances: u32,
+		firstvertex: u32, firstinstance: u32)
+	{
+		let connection = self.vulkan().get();
+
+		(connection.draw)(self.cmdbuf.buffer, nvertices, ninstances,
+			firstvertex, firstinstance);
+	}
+
+	/// Set push constants as in `cmd_push_constants()`.
+	///
+	/// # Safety
+	/// `T` must not have padding, and `offset` plus its size must be within
+	/// a range of `pipeline_layout` for `stages`, which must be the
+	/// layout of the bound pipeline (or compatible with it).
+	pub unsafe fn push_constants<T: Copy>(&mut self,
+		pipeline_layout: VkPipelineLayout, stages: Stages, offset: u32,
+		data: &T)
+	{
+		let connection = self.vulkan().get();
+
+		::push_constants(&connection, self.cmdbuf.buffer,
+			pipeline_layout, stages, offset, data);
+	}
+
+	/// Bind the instance buffer for an instanced `style`.
+	///
+	/// # Safety
+	/// `instances` must hold an instance for each that is drawn, and live
+	/// until the command buffer has finished executing.
+	pub unsafe fn bind_instances(&mut self, style: &Style,
+		instances: VkBuffer)
+	{
+		let connection = self.vulkan().get();
+
+		::bind_instances(&connection, self.cmdbuf.buffer, style,
+			instances);
+	}
+
+	/// Bind an index buffer for the following indexed draws.
+	///
+	/// # Safety
+	/// `offset` must be a multiple of the index size, and `index_buffer` must
+	/// live until the command buffer has finished executing.
+	pub unsafe fn bind_ib(&mut self, index_buffer: VkBuffer, offset: u64,
+		index_type: IndexType)
+	{
+		let connection = self.vulkan().get();
+
+		(connection.bind_ib)(self.cmdbuf.buffer, index_buffer, offset,
+			index_type.vk());
+	}
+
+	/// Draw `nindices` vertices from the bound index buffer, `ninstances`
+	/// times.
+	///
+	/// # Safety
+	/// A pipeline and what it reads must be bound, the indices must be
+	/// within the bound index buffer, and (once offset by `vertexoffset`)
+	/// refer to vertices within the bound vertex buffers.
+	pub unsafe fn draw_indexed(&mut self, nindices: u32, ninstances: u32,
+		firstindex: u32, vertexoffset: i32, firstinstance: u32)
+	{
+		let connection = self.vulkan().get();
+
+		(connection.draw_indexed)(self.cmdbuf.buffer, nindices,
+			ninstances, firstindex, vertexoffset, firstinstance);
+	}
+
+	/// Draw as in `cmd_draw_indirect()`.
+	///
+	/// # Safety
+	/// `buffer` must hold `ndraws` commands from `offset` (a multiple of 4),
+	/// each within the bound vertex buffers, and must live until the
+	/// command buffer has finished executing.
+	pub unsafe fn draw_indirect(&mut self, buffer: VkBuffer, offset: u64,
+		ndraws: u32)
+	{
+		let connection = self.vulkan().get();
+
+		::draw_indirect(&connection, self.cmdbuf.buffer, buffer, offset,
+			ndraws, false);
+	}
+
+	/// Draw as in `cmd_draw_indexed_indirect()`.
+	///
+	/// # Safety
+	/// `buffer` must hold `ndraws` commands from `offset` (a multiple of 4),
+	/// each with indices within the bound index buffer that refer to
+	/// vertices within the bound vertex buffers.  `buffer` must live until
+	/// the command buffer has finished executing.
+	pub unsafe fn draw_indexed_indirect(&mut self, buffer: VkBuffer,
+		offset: u64, ndraws: u32)
+	{
+		let connection = self.vulkan().get();
+
+		::draw_indirect(&connection, self.cmdbuf.buffer, buffer, offset,
+			ndraws, true);
+	}
+
+	/// Draw as in `cmd_draw_indirect_count()`.
+	///
+	/// # Safety
+	/// `buffer` must hold `max_draws` commands from `offset` (a multiple of
+	/// 4), each within the bound vertex buffers, and `count_offset` must be
+	/// a multiple of 4 within `count_buffer`.  Both buffers must live until
+	/// the command buffer has finished executing.
+	pub unsafe fn draw_indirect_count(&mut self, buffer: VkBuffer,
+		offset: u64, count_buffer: VkBuffer, count_offset: u64,
+		max_draws: u32)
+	{
+		let connection = self.vulkan().get();
+
+		::draw_indirect_count(&connection, self.cmdbuf.buffer, buffer,
+			offset, count_buffer, count_offset, max_draws, false);
+	}
+
+	/// Draw as in `cmd_draw_indexed_indirect_count()`.
+	///
+	/// # Safety
+	/// `buffer` must hold `max_draws` commands from `offset` (a multiple of
+	/// 4), each with indices within the bound index buffer that refer to
+	/// vertices within the bound vertex buffers, and `count_offset` must be
+	/// a multiple of 4 within `count_buffer`.  Both buffers must live until
+	/// the command buffer has finished executing.
+	pub unsafe fn draw_indexed_indirect_count(&mut self, buffer: VkBuffer,
+		offset: u64, count_buffer: VkBuffer, count_offset: u64,
+		max_draws: u32)
+	{
+		let connection = self.vulkan().get();
+
+		::draw_indirect_count(&connection, self.cmdbuf.buffer, buffer,
+			offset, count_buffer, count_offset, max_draws, true);
+	}
+
+	/// Set `viewports`, starting at index `first`.
+	///
+	/// # Safety
+	/// Each viewport must have a positive width and height, and a depth
+	/// range within 0.0 to 1.0.
+	pub unsafe fn set_viewports(&mut self, first: u32,
+		viewports: &[Viewport])
+	{
+		let connection = self.vulkan().get();
+
+		::set_viewports(&connection, self.cmdbuf.buffer, first,
+			viewports);
+	}
+
+	/// Set `scissors`, starting at index `first`.
+	///
+	/// # Safety
+	/// Each rectangle must have a non-negative `x` and `y`, and its far
+	/// corner must fit in an `i32`.
+	pub unsafe fn set_scissors(&mut self, first: u32, scissors: &[Scissor]) {
+		let connection = self.vulkan().get();
+
+		::set_scissors(&connection, self.cmdbuf.buffer, first, scissors);
+	}
+
+	/// Clear a rectangle of attachments, as in `cmd_clear_attachments()`.
+	///
+	/// # Safety
+	/// `rect` must be inside of the render area, and each index must be of
+	/// a color attachment of the render pass (ignored for depth).
+	pub unsafe fn clear_attachments(&mut self, clear: &[(u32, Clear)],
+		rect: Scissor)
+	{
+		let connection = self.vulkan().get();
+
+		::clear_attachments(&connection, self.cmdbuf.buffer, clear,
+			rect);
+	}
+
+	/// Finish the render pass.
+	///
+	/// # Safety
+	/// Queries begun inside of the render pass must have been ended.
+	pub unsafe fn end_render_pass(self) -> Recorder<'a> {
+		{
+			let connection = self.vulkan().get();
+
+			(connection.end_render_pass)(self.cmdbuf.buffer);
+		}
+
+		Recorder { cmdbuf: self.cmdbuf }
+	}
+
+	fn vulkan(&self) -> &Gpu {
+		&self.cmdbuf.pool.0.vulkan
+	}
+}
+
+/// A render pass of a primary command buffer that is drawn by executing
+/// secondary command buffers, recorded on any thread.
+pub struct SecondaryRenderPass<'a> {
+	cmdbuf: &'a mut CommandBuffer,
+}
+
+impl<'a> SecondaryRenderPass<'a> {
+	/// Execute secondary command buffers, which must have been recorded
+	/// for this render pass (or a compatible one) and framebuffer.
+	///
+	/// # Safety
+	/// `buffers` must have finished recording, and must not be executing
+	/// anywhere else.
+	pub unsafe fn execute(&mut self, buffers: &[SecondaryCommandBuffer]) {
+		let buffers: Vec<VkCommandBuffer> = buffers.iter()
+			.map(|buffer| buffer.buffer)
+			.collect();
+		let connection = self.cmdbuf.pool.0.vulkan.get();
+
+		(connection.execute_commands)(self.cmdbuf.buffer,
+			buffers.len() as u32, buffers.as_ptr());
+	}
+
+	/// Finish the render pass.
+	///
+	/// # Safety
+	/// Executed command buffers must live until this one has finished
+	/// executing.
+	pub unsafe fn end_render_pass(self) -> Recorder<'a> {
+		{
+			let connection = self.cmdbuf.pool.0.vulkan.get();
+
+			(connection.end_render_pass)(self.cmdbuf.buffer);
+		}
+
+		Recorder { cmdbuf: self.cmdbuf }
+	}
+}
+
+/// The device and the functions needed to record secondary command buffers,
+/// copied from the `Gpu` (which can't be shared between threads).
+#[derive(Copy, Clone)] struct Commands {
+	device: VkDevice,
+	max_viewports: u32,
+	max_vertex_input_bindings: u32,
+	multi_draw_indirect: bool,
+	max_draw_indirect_count: u32,
+	drop_cmdpool: unsafe extern "system" fn(VkDevice, u64, *const c_void)
+		-> (),
+	new_cmdbuffs: unsafe extern "system" fn(VkDevice,
+		*const VkCommandBufferAllocateInfo, *mut VkCommandBuffer)
+		-> VkResult,
+	begin_cmdbuff: unsafe extern "system" fn(VkCommandBuffer,
+		*const VkCommandBufferBeginInfo) -> VkResult,
+	end_cmdbuff: unsafe extern "system" fn(VkCommandBuffer) -> VkResult,
+	bind_pipeline: unsafe extern "system" fn(VkCommandBuffer,
+		VkPipelineBindPoint, VkPipeline) -> (),
+	bind_descsets: unsafe extern "system" fn(VkCommandBuffer,
+		VkPipelineBindPoint, VkPipelineLayout, u32, u32,
+		*const VkDescriptorSet, u32, *const u32) -> (),
+	bind_vb: unsafe extern "system" fn(VkCommandBuffer, u32, u32,
+		*const VkBuffer, *const VkDeviceSize) -> (),
+	push_constants: unsafe extern "system" fn(VkCommandBuffer,
+		VkPipelineLayout, VkFlags, u32, u32, *const c_void) -> (),
+	draw: unsafe extern "system" fn(VkCommandBuffer, u32, u32, u32, u32)
+		-> (),
+	draw_indexed: unsafe extern "system" fn(VkCommandBuffer, u32, u32, u32,
+		i32, u32) -> (),
+	bind_ib: unsafe extern "system" fn(VkCommandBuffer, VkBuffer,
+		VkDeviceSize, VkIndexType) -> (),
+	draw_indirect: unsafe extern "system" fn(VkCommandBuffer, VkBuffer,
+		VkDeviceSize, u32, u32) -> (),
+	draw_indexed_indirect: unsafe extern "system" fn(VkCommandBuffer,
+		VkBuffer, VkDeviceSize, u32, u32) -> (),
+	draw_indirect_count: Option<unsafe extern "system" fn(VkCommandBuffer,
+		VkBuffer, VkDeviceSize, VkBuffer, VkDeviceSize, u32, u32) -> ()>,
+	draw_indexed_indirect_count: Option<unsafe extern "system" fn(
+		VkCommandBuffer, VkBuffer, VkDeviceSize, VkBuffer, VkDeviceSize,
+		u32, u32) -> ()>,
+	set_viewport: unsafe extern "system" fn(VkCommandBuffer, u32, u32,
+		*const VkViewport) -> (),
+	set_scissor: unsafe extern "system" fn(VkCommandBuffer, u32, u32,
+		*const VkRect2D) -> (),
+	set_blend_constants: unsafe extern "system" fn(VkCommandBuffer,
+		*const [f32; 4]) -> (),
+}
+
+/// A command pool for one worker thread to record `SecondaryCommandBuffer`s
+/// with.  Create one per thread on the main thread, and send it to the
+/// worker.  The `VkCommandPool` is freed once the pool and all of its command
+/// buffers are dropped.
+pub struct SecondaryPool(Arc<SecondaryPoolContext>);
+
+struct SecondaryPoolContext {
+	pool: u64,
+	commands: Commands,
+}
+
+// The pool is only used through the `SecondaryPool` (which is borrowed mutably
+// while recording) and when the last reference is dropped.
+unsafe impl Send for SecondaryPoolContext {}
+unsafe impl Sync for SecondaryPoolContext {}
+
+impl SecondaryPool {
+	/// Create a command pool for secondary command buffers.
+	///
+	/// # Safety
+	/// The pool copies the device and its functions, because the `Gpu`
+	/// can't be sent to other threads.  So, `vulkan` must not be dropped
+	/// until this pool and all of its command buffers are dropped.
+	pub unsafe fn new(vulkan: &Gpu) -> Self {
+		let connection = vulkan.get();
+		let mut pool = 0;
+
+		(connection.new_cmdpool)(
+			connection.device,
+			&VkCommandPoolCreateInfo {
+				s_type: VkStructureType::CommandPoolCreateInfo,
+				p_next: null_mut(),
+				flags: 0x00000002, // Reset Command Buffer
+				queue_family_index: connection.queue_family,
+			},
+			null(),
+			&mut pool
+		).unwrap();
+
+		SecondaryPool(Arc::new(SecondaryPoolContext {
+			pool,
+			commands: Commands {
+				device: connection.device,
+				max_viewports: connection.max_viewports(),
+				max_vertex_input_bindings: connection.properties
+					.limits.max_vertex_input_bindings,
+				multi_draw_indirect: connection.features
+					.multi_draw_indirect != 0,
+				max_draw_indirect_count: connection.properties
+					.limits.max_draw_indirect_count,
+				drop_cmdpool: connection.drop_cmdpool,
+				new_cmdbuffs: connection.new_cmdbuffs,
+				begin_cmdbuff: connection.begin_cmdbuff,
+				end_cmdbuff: connection.end_cmdbuff,
+				bind_pipeline: connection.bind_pipeline,
+				bind_descsets: connection.bind_descsets,
+				bind_vb: connection.bind_vb,
+				push_constants: connection.push_constants,
+				draw: connection.draw,
+				draw_indexed: connection.draw_indexed,
+				bind_ib: connection.bind_ib,
+				draw_indirect: connection.draw_indirect,
+				draw_indexed_indirect:
+					connection.draw_indexed_indirect,
+				draw_indirect_count:
+					connection.draw_indirect_count,
+				draw_indexed_indirect_count:
+					connection.draw_indexed_indirect_count,
+				set_viewport: connection.set_viewport,
+				set_scissor: connection.set_scissor,
+				set_blend_constants:
+					connection.set_blend_constants,
+			},
+		}))
+	}
+
+	/// Allocate a new secondary command buffer, which is freed along with
+	/// this pool.
+	pub fn command_buffer(&mut self) -> SecondaryCommandBuffer { unsafe {
+		let mut buffer = null_mut();
+
+		(self.0.commands.new_cmdbuffs)(
+			self.0.commands.device,
+			&VkCommandBufferAllocateInfo {
+				s_type: VkStructureType::CommandBufferAllocateInfo,
+				p_next: null_mut(),
+				command_pool: self.0.pool,
+				level: VkCommandBufferLevel::Secondary,
+				command_buffer_count: 1,
+			},
+			&mut buffer
+		).unwrap();
+
+		SecondaryCommandBuffer { buffer, pool: self.0.clone() }
+	} }
+
+	/// Start recording `buffer` (allocated from this pool) to be executed
+	/// inside of `render_pass` on `framebuffer`, which is `width` by
+	/// `height`.  Viewports and scissors are reset to cover the framebuffer.
+	///
+	/// # Safety
+	/// `buffer` must not be executing, and `framebuffer` must be for
+	/// `render_pass` (or a compatible one).
+	pub unsafe fn begin(&mut self, buffer: &SecondaryCommandBuffer,
+		render_pass: VkRenderPass, framebuffer: VkFramebuffer,
+		width: u32, height: u32) -> SecondaryRecorder<'_>
+	{
+		assert!(Arc::ptr_eq(&self.0, &buffer.pool),
+			"Command buffer wasn't allocated from this pool");
+
+		(self.0.commands.begin_cmdbuff)(buffer.buffer,
+			&VkCommandBufferBeginInfo
+		{
+			s_type: VkStructureType::CommandBufferBeginInfo,
+			p_next: null(),
+			flags: VkCommandBufferUsage::OneTimeSubmitRenderPassContinue,
+			p_inheritance_info: &VkCommandBufferInheritanceInfo {
+				s_type: VkStructureType::CommandBufferInheritanceInfo,
+				p_next: null(),
+				render_pass,
+				subpass: 0,
+				framebuffer,
+				occlusion_query_enable: 0,
+				query_flags: 0,
+				pipeline_statistics: 0,
+			},
+		}).unwrap();
+
+		let mut recorder = SecondaryRecorder {
+			pool: self, buffer: buffer.buffer,
+		};
+
+		let count = recorder.pool.0.commands.max_viewports as usize;
+		recorder.set_viewports(0, &vec![Viewport::new(0.0, 0.0,
+			width as f32, height as f32); count]);
+		recorder.set_scissors(0, &vec![Scissor::new(0, 0, width, height);
+			count]);
+
+		recorder
+	}
+}
+
+impl Drop for SecondaryPoolContext {
+	fn drop(&mut self) {
+		unsafe {
+			(self.commands.drop_cmdpool)(self.commands.device,
+				self.pool, null());
+		}
+	}
+}
+
+/// A secondary `VkCommandBuffer`, allocated from a `SecondaryPool`, which it
+/// keeps from being freed.
+#[derive(Clone)] pub struct SecondaryCommandBuffer {
+	buffer: VkCommandBuffer,
+	pool: Arc<SecondaryPoolContext>,
+}
+
+unsafe impl Send for SecondaryCommandBuffer {}
+
+/// Records commands into a secondary command buffer.  Call `end()` when
+/// finished.  The pool is borrowed while recording, because Vulkan doesn't
+/// allow using a command pool from more than one thread at a time.
+pub struct SecondaryRecorder<'a> {
+	pool: &'a mut SecondaryPool,
+	buffer: VkCommandBuffer,
+}
+
+impl<'a> SecondaryRecorder<'a> {
+	/// Bind a graphics pipeline for the following draws.
+	///
+	/// # Safety
+	/// `pipeline` must be for a render pass compatible with the one passed to
+	/// `SecondaryPool::begin()`.
+	pub unsafe fn bind_pipeline(&mut self, pipeline: VkPipeline) {
+		(self.pool.0.commands.bind_pipeline)(self.buffer,
+			VkPipelineBindPoint::Graphics, pipeline);
+	}
+
+	/// Bind a descriptor set for the following draws.
+	///
+	/// # Safety
+	/// `desc_set` must match set 0 of `pipeline_layout`, and must not be
+	/// updated or dropped until every command buffer that executes this one
+	/// has finished executing.
+	pub unsafe fn bind_descsets(&mut self,
+		pipeline_layout: VkPipelineLayout, desc_set: VkDescriptorSet)
+	{
+		(self.pool.0.commands.bind_descsets)(self.buffer,
+			VkPipelineBindPoint::Graphics, pipeline_layout, 0, 1,
+			[desc_set].as_ptr(), 0, null());
+	}
+
+	/// Bind descriptor set `first_set`, as in `cmd_bind_descsets_at()`.
+	///
+	/// # Safety
+	/// `desc_set` must match set `first_set` of `pipeline_layout`, and must
+	/// not be updated or dropped until every command buffer that executes
+	/// this one has finished executing.
+	pub unsafe fn bind_descsets_at(&mut self,
+		pipeline_layout: VkPipelineLayout, first_set: u32,
+		desc_set: VkDescriptorSet)
+	{
+		(self.pool.0.commands.bind_descsets)(self.buffer,
+			VkPipelineBindPoint::Graphics, pipeline_layout,
+			first_set, 1, [desc_set].as_ptr(), 0, null());
+	}
+
+	/// Bind vertex buffers for the following draws.
+	///
+	/// # Safety
+	/// The buffers must match the bound pipeline's vertex bindings, and live
+	/// until every command buffer that executes this one has finished
+	/// executing.
+	pub unsafe fn bind_vb(&mut self, vertex_buffers: &[VkBuffer]) {
+		self.bind_vb_offsets(0, vertex_buffers,
+			&vec![0; vertex_buffers.len()]);
+	}
+
+	/// Set push constants as in `cmd_push_constants()`.
+	///
+	/// # Safety
+	/// `T` must not have padding, and `offset` plus its size must be within
+	/// a range of `pipeline_layout` for `stages`, which must be the
+	/// layout of the bound pipeline (or compatible with it).
+	pub unsafe fn push_constants<T: Copy>(&mut self,
+		pipeline_layout: VkPipelineLayout, stages: Stages, offset: u32,
+		data: &T)
+	{
+		(self.pool.0.commands.push_constants)(self.buffer, pipeline_layout,
+			stages.0, offset, mem::size_of::<T>() as u32,
+			data as *const T as *const c_void);
+	}
+
+	/// Bind vertex buffers as in `cmd_bind_vb_offsets()`.
+	///
+	/// # Safety
+	/// The buffers must match the bound pipeline's vertex bindings from
+	/// `first`, each offset must be within its buffer, and the buffers must
+	/// live until every command buffer that executes this one has finished
+	/// executing.
+	pub unsafe fn bind_vb_offsets(&mut self, first: u32,
+		vertex_buffers: &[VkBuffer], offsets: &[u64])
+	{
+		let max = self.pool.0.commands.max_vertex_input_bindings;
+
+		assert_eq!(vertex_buffers.len(), offsets.len(),
+			"Need an offset for each vertex buffer");
+		assert!(!vertex_buffers.is_empty() && ::range_end(first,
+			vertex_buffers.len()).is_some_and(|end| end <= max),
+			"Wrong number of vertex buffers");
+
+		(self.pool.0.commands.bind_vb)(self.buffer, first,
+			vertex_buffers.len() as u32, vertex_buffers.as_ptr(),
+			offsets.as_ptr());
+	}
+
+	/// Bind the instance buffer for an instanced `style`.
+	///
+	/// # Safety
+	/// `instances` must hold an instance for each that is drawn, and live
+	/// until every command buffer that executes this one has finished
+	/// executing.
+	pub unsafe fn bind_instances(&mut self, style: &Style,
+		instances: VkBuffer)
+	{
+		let binding = style.instance_binding()
+			.expect("Style doesn't have per-instance attributes");
+
+		(self.pool.0.commands.bind_vb)(self.buffer, binding, 1,
+			&instances, &0);
+	}
+
+	/// Draw `nvertices` vertices, `ninstances` times.
+	///
+	/// # Safety
+	/// A pipeline and what it reads must be bound in this command buffer
+	/// (nothing is inherited from the one executing it), and the vertices
+	/// must be within the bound vertex buffers.
+	pub unsafe fn draw(&mut self, nvertices: u32, ninstances: u32,
+		firstvertex: u32, firstinstance: u32)
+	{
+		(self.pool.0.commands.draw)(self.buffer, nvertices, ninstances,
+			firstvertex, firstinstance);
+	}
+
+	/// Bind an index buffer for the following indexed draws.
+	///
+	/// # Safety
+	/// `offset` must be a multiple of the index size, and `index_buffer` must
+	/// live until the command buffer has finished executing.
+	pub unsafe fn bind_ib(&mut self, index_buffer: VkBuffer, offset: u64,
+		index_type: IndexType)
+	{
+		(self.pool.0.commands.bind_ib)(self.buffer, index_buffer, offset,
+			index_type.vk());
+	}
+
+	/// Draw `nindices` vertices from the bound index buffer, `ninstances`
+	/// times.
+	///
+	/// # Safety
+	/// A pipeline and what it reads must be bound, the indices must be
+	/// within the bound index buffer, and (once offset by `vertexoffset`)
+	/// refer to vertices within the bound vertex buffers.
+	pub unsafe fn draw_indexed(&mut self, nindices: u32, ninstances: u32,
+		firstindex: u32, vertexoffset: i32, firstinstance: u32)
+	{
+		(self.pool.0.commands.draw_indexed)(self.buffer, nindices,
+			ninstances, firstindex, vertexoffset, firstinstance);
+	}
+
+	/// Draw as in `cmd_draw_indirect()`.
+	///
+	/// # Safety
+	/// `buffer` must hold `ndraws` commands from `offset` (a multiple of 4),
+	/// each within the bound vertex buffers, and must live until every
+	/// command buffer that executes this one has finished executing.
+	pub unsafe fn draw_indirect(&mut self, buffer: VkBuffer, offset: u64,
+		ndraws: u32)
+	{
+		self.check_indirect(ndraws);
+
+		(self.pool.0.commands.draw_indirect)(self.buffer, buffer, offset,
+			ndraws, mem::size_of::<DrawIndirectCommand>() as u32);
+	}
+
+	/// Draw as in `cmd_draw_indexed_indirect()`.
+	///
+	/// # Safety
+	/// `buffer` must hold `ndraws` commands from `offset` (a multiple of 4),
+	/// each with indices within the bound index buffer that refer to
+	/// vertices within the bound vertex buffers.  `buffer` must live until
+	/// every command buffer that executes this one has finished executing.
+	pub unsafe fn draw_indexed_indirect(&mut self, buffer: VkBuffer,
+		offset: u64, ndraws: u32)
+	{
+		self.check_indirect(ndraws);
+
+		(self.pool.0.commands.draw_indexed_indirect)(self.buffer, buffer,
+			offset, ndraws,
+			mem::size_of::<DrawIndexedIndirectCommand>() as u32);
+	}
+
+	/// Draw as in `cmd_draw_indirect_count()`.
+	///
+	/// # Safety
+	/// `buffer` must hold `max_draws` commands from `offset` (a multiple of
+	/// 4), each within the bound vertex buffers, and `count_offset` must be
+	/// a multiple of 4 within `count_buffer`.  Both buffers must live until
+	/// every command buffer that executes this one has finished executing.
+	pub unsafe fn draw_indirect_count(&mut self, buffer: VkBuffer,
+		offset: u64, count_buffer: VkBuffer, count_offset: u64,
+		max_draws: u32)
+	{
+		let draw = self.pool.0.commands.draw_indirect_count
+			.expect("GPU doesn't support indirect draw count");
+
+		draw(self.buffer, buffer, offset, count_buffer, count_offset,
+			max_draws, mem::size_of::<DrawIndirectCommand>() as u32);
+	}
+
+	/// Draw as in `cmd_draw_indexed_indirect_count()`.
+	///
+	/// # Safety
+	/// `buffer` must hold `max_draws` commands from `offset` (a multiple of
+	/// 4), each with indices within the bound index buffer that refer to
+	/// vertices within the bound vertex buffers, and `count_offset` must be
+	/// a multiple of 4 within `count_buffer`.  Both buffers must live until
+	/// every command buffer that executes this one has finished executing.
+	pub unsafe fn draw_indexed_indirect_count(&mut self, buffer: VkBuffer,
+		offset: u64, count_buffer: VkBuffer, count_offset: u64,
+		max_draws: u32)
+	{
+		let draw = self.pool.0.commands.draw_indexed_indirect_count
+			.expect("GPU doesn't support indirect draw count");
+
+		draw(self.buffer, buffer, offset, count_buffer, count_offset,
+			max_draws,
+			mem::size_of::<DrawIndexedIndirectCommand>() as u32);
+	}
+
+	/// Check that the GPU can draw `ndraws` indirect draws at once.
+	fn check_indirect(&self, ndraws: u32) {
+		let commands = &self.pool.0.commands;
+
+		assert!(ndraws <= 1 || commands.multi_draw_indirect,
+			"GPU doesn't support multi-draw indirect");
+		assert!(ndraws <= commands.max_draw_indirect_count,
+			"Too many indirect draws");
+	}
+
+	/// Set `viewports`, starting at index `first`.
+	///
+	/// # Safety
+	/// Each viewport must have a positive width and height, and a depth
+	/// range within 0.0 to 1.0.
+	pub unsafe fn set_viewports(&mut self, first: u32,
+		viewports: &[Viewport])
+	{
+		let max = self.pool.0.commands.max_viewports;
+
+		assert!(::range_end(first, viewports.len())
+			.is_some_and(|end| end <= max),
+			"GPU supports only {} viewport(s)", max);
+
+		let viewports: Vec<VkViewport> = viewports.iter()
+			.map(|viewport| viewport.vk())
+			.collect();
+
+		(self.pool.0.commands.set_viewport)(self.buffer, first,
+			viewports.len() as u32, viewports.as_ptr());
+	}
+
+	/// Set `scissors`, starting at index `first`.
+	///
+	/// # Safety
+	/// Each rectangle must have a non-negative `x` and `y`, and its far
+	/// corner must fit in an `i32`.
+	pub unsafe fn set_scissors(&mut self, first: u32, scissors: &[Scissor]) {
+		let max = self.pool.0.commands.max_viewports;
+
+		assert!(::range_end(first, scissors.len())
+			.is_some_and(|end| end <= max),
+			"GPU supports only {} viewport(s)", max);
+
+		let scissors: Vec<VkRect2D> = scissors.iter()
+			.map(|scissor| scissor.vk())
+			.collect();
+
+		(self.pool.0.commands.set_scissor)(self.buffer, first,
+			scissors.len() as u32, scissors.as_ptr());
+	}
+
+	/// Set the blend constants, as in `cmd_set_blend_constants()`.
+	pub fn set_blend_constants(&mut self, rgba: [f32; 4]) { unsafe {
+		(self.pool.0.commands.set_blend_constants)(self.buffer, &rgba);
+	} }
+
+	/// Finish recording, so the command buffer can be executed.
+	///
+	/// # Safety
+	/// The commands recorded must be valid inside of the render pass, which
+	/// isn't all checked.
+	pub unsafe fn end(self) {
+		(self.pool.0.commands.end_cmdbuff)(self.buffer).unwrap();
+	}
+}
@@ -0,0 +1,264 @@
+// "asi_vulkan" - Aldaron's System Interface - Vulkan
+//
+// Copyright Jeron A. Lau 2018.
+// Distributed under the Boost Software License, Version 1.0.
+// (See accompanying file LICENSE_1_0.txt or copy at
+// https://www.boost.org/LICENSE_1_0.txt)
+
+use Stages;
+use types::{ VkBool32, VkPhysicalDeviceFeatures };
+use naga::{ self, SourceLocation };
+use naga::back::spv;
+use naga::front::{ glsl, wgsl };
+use naga::valid::{ Capabilities, ValidationFlags, Validator };
+use std::{ fmt, error::Error };
+
+/// The language of shader source for `compile()`.
+#[derive(Copy, Clone, Debug, PartialEq)] pub enum Language {
+	/// Vulkan GLSL for one stage (vertex, fragment or compute), with the
+	/// entry point `main`.
+	Glsl(Stages),
+	/// WGSL, which declares the stage of each entry point.
+	Wgsl,
+}
+
+/// An error in shader source found by `compile()`.
+#[derive(Clone, Debug, PartialEq)] pub struct CompileError {
+	/// Line of the error, starting at 1 (0 if unknown).
+	pub line: u32,
+	/// Column of the error in bytes, starting at 1 (0 if unknown).
+	pub column: u32,
+	pub message: String,
+}
+
+impl CompileError {
+	pub(crate) fn new(location: Option<SourceLocation>, message: String)
+		-> Self
+	{
+		let (line, column) = location.map(|l| {
+			(l.line_number, l.line_position)
+		}).unwrap_or((0, 0));
+
+		CompileError { line, column, message }
+	}
+}
+
+impl fmt::Display for CompileError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "{}:{}: {}", self.line, self.column, self.message)
+	}
+}
+
+/// Get an error's message, followed by the messages of its causes.
+fn message(error: &dyn Error) -> String {
+	let mut message = error.to_string();
+	let mut source = error.source();
+
+	while let Some(error) = source {
+		message.push_str(": ");
+		message.push_str(&error.to_string());
+		source = error.source();
+	}
+
+	message
+}
+
+/// Get what shaders can use on every Vulkan GPU.
+fn core_capabilities() -> Capabilities {
+	Capabilities::PUSH_CONSTANT | Capabilities::EARLY_DEPTH_TEST
+}
+
+/// Get what shaders can use on a GPU with the enabled `features`.
+pub(crate) fn capabilities(features: &VkPhysicalDeviceFeatures)
+	-> Capabilities
+{
+	let mut capabilities = core_capabilities();
+	let optional: [(VkBool32, Capabilities); 9] = [
+		(features.shader_float64, Capabilities::FLOAT64),
+		(features.shader_int64, Capabilities::SHADER_INT64),
+		(features.geometry_shader, Capabilities::PRIMITIVE_INDEX),
+		(features.shader_clip_distance, Capabilities::CLIP_DISTANCE),
+		(features.shader_cull_distance, Capabilities::CULL_DISTANCE),
+		(features.sample_rate_shading,
+			Capabilities::MULTISAMPLED_SHADING),
+		(features.image_cube_array, Capabilities::CUBE_ARRAY_TEXTURES),
+		(features.dual_src_blend, Capabilities::DUAL_SOURCE_BLENDING),
+		(features.shader_storage_image_extended_formats,
+			Capabilities::STORAGE_TEXTURE_16BIT_NORM_FORMATS),
+	];
+
+	for &(enabled, capability) in optional.iter() {
+		if enabled != 0 {
+			capabilities |= capability;
+		}
+	}
+
+	capabilities
+}
+
+/// Compile shader `source` to SPIR-V, for `ShaderModule::from_words()`.
+/// It may only use what every Vulkan GPU supports, `ShaderModule::compile()`
+/// also allows what the GPU's enabled features do.
+pub fn compile(source: &str, language: Language)
+	-> Result<Vec<u32>, Vec<CompileError>>
+{
+	compile_with(source, language, core_capabilities())
+}
+
+/// Compile shader `source` to SPIR-V that may use `capabilities`.
+pub(crate) fn compile_with(source: &str, language: Language,
+	capabilities: Capabilities) -> Result<Vec<u32>, Vec<CompileError>>
+{
+	let error = |message: String| vec![CompileError::new(None, message)];
+	let mut options = spv::Options::default();
+
+	let module = match language {
+		Language::Glsl(stage) => {
+			let stage = match stage {
+				Stages::VERTEX => naga::ShaderStage::Vertex,
+				Stages::FRAGMENT => naga::ShaderStage::Fragment,
+				Stages::COMPUTE => naga::ShaderStage::Compute,
+				_ => return Err(error(format!(
+					"Can't compile {:?} GLSL shaders", stage))),
+			};
+
+			// GLSL is already in Vulkan's coordinate space.
+			options.flags.remove(spv::WriterFlags::ADJUST_COORDINATE_SPACE);
+
+			glsl::Frontend::default()
+				.parse(&glsl::Options::from(stage), source)
+				.map_err(|errors| errors.errors.iter().map(|e| {
+					CompileError::new(e.location(source),
+						message(&e.kind))
+				}).collect::<Vec<_>>())?
+		}
+		Language::Wgsl => wgsl::parse_str(source).map_err(|e| {
+			vec![CompileError::new(e.location(source),
+				e.message().to_string())]
+		})?,
+	};
+
+	let info = Validator::new(ValidationFlags::all(), capabilities)
+		.validate(&module).map_err(|e| {
+			let location = e.location(source);
+
+			vec![CompileError::new(location, message(&e.into_inner()))]
+		})?;
+
+	spv::write_vec(&module, &info, &options, None)
+		.map_err(|e| error(message(&e)))
+}
+
+#[cfg(all(test, feature = "compiler"))]
+mod tests {
+	use super::*;
+	use reflect;
+	use Descriptor;
+	use VertexFormat;
+	use std::mem;
+
+	const VERTEX: &str = "#version 450
+layout(location = 0) in vec4 position;
+layout(location = 0) out vec2 uv;
+layout(set = 0, binding = 0) uniform Transform { mat4 matrix; };
+
+void main() {
+	uv = position.xy;
+	gl_Position = matrix * position;
+}
+";
+
+	const FRAGMENT: &str = "
+struct Material { color: vec4<f32> }
+
+@group(0) @binding(1) var<uniform> material: Material;
+
+@fragment
+fn main(@location(0) uv: vec2<f32>) -> @location(0) vec4<f32> {
+	return material.color * vec4<f32>(uv, 0.0, 1.0);
+}
+";
+
+	#[test]
+	fn glsl_error() {
+		let errors = compile("#version 450
+void main() {
+	gl_Position = vec4(1.0) + ;
+}
+", Language::Glsl(Stages::VERTEX)).unwrap_err();
+
+		assert_eq!((errors[0].line, errors[0].column), (3, 28));
+	}
+
+	#[test]
+	fn wgsl_error() {
+		let errors = compile("
+@fragment
+fn main() -> @location(0) vec4<f32> {
+	return vec4<f32>(1.0) +;
+}
+", Language::Wgsl).unwrap_err();
+
+		assert_eq!((errors[0].line, errors[0].column), (4, 25));
+	}
+
+	/// Compile `source`, and check that it's valid SPIR-V with one entry
+	/// point for `stage`.
+	fn compiled(source: &str, language: Language, stage: Stages)
+		-> reflect::Reflection
+	{
+		let words = compile(source, language).unwrap();
+		let bytes: Vec<u8> = words.iter()
+			.flat_map(|w| w.to_ne_bytes().to_vec()).collect();
+
+		assert_eq!(reflect::words(&bytes).unwrap(), words);
+		assert_eq!(reflect::entry_points(&words),
+			vec![(stage, "main".to_string())]);
+
+		reflect::reflect(&words).unwrap()
+	}
+
+	#[test]
+	fn spirv() {
+		let vertex = compiled(VERTEX, Language::Glsl(Stages::VERTEX),
+			Stages::VERTEX);
+		let fragment = compiled(FRAGMENT, Language::Wgsl,
+			Stages::FRAGMENT);
+
+		let descriptors: Vec<(u32, u32, Descriptor)> = vertex.descriptors
+			.iter().chain(fragment.descriptors.iter())
+			.map(|d| (d.set, d.binding, d.descriptor)).collect();
+
+		assert_eq!(descriptors, vec![(0, 0, Descriptor::Uniform),
+			(0, 1, Descriptor::Uniform)]);
+
+		let vertex = vertex.entry_point(Stages::VERTEX).unwrap();
+		let fragment = fragment.entry_point(Stages::FRAGMENT).unwrap();
+
+		assert_eq!(vertex.inputs.len(), 1);
+		assert_eq!(vertex.inputs[0].location, 0);
+		assert_eq!(vertex.inputs[0].ty.vertex_format(),
+			Some(VertexFormat::Vec4));
+		assert!(reflect::interface_mismatches(vertex, fragment)
+			.is_empty());
+	}
+
+	#[test]
+	fn features() {
+		let source = "
+@compute @workgroup_size(1)
+fn main() {
+	var x: f64 = 1.0lf;
+}
+";
+		let mut features: VkPhysicalDeviceFeatures
+			= unsafe { mem::zeroed() };
+
+		assert!(compile(source, Language::Wgsl).is_err());
+
+		features.shader_float64 = 1;
+
+		assert!(compile_with(source, Language::Wgsl,
+			capabilities(&features)).is_ok());
+	}
+}
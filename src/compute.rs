@@ -0,0 +1,203 @@
+// "asi_vulkan" - Aldaron's System Interface - Vulkan
+//
+// Copyright Jeron A. Lau 2018.
+// Distributed under the Boost Software License, Version 1.0.
+// (See accompanying file LICENSE_1_0.txt or copy at
+// https://www.boost.org/LICENSE_1_0.txt)
+
+use null;
+use mem;
+
+use Gpu;
+use ShaderModule;
+use PipelineCache;
+use Specialization;
+use Stages;
+use descriptor::{ self, Descriptor };
+use types::*;
+use std::{ rc::Rc };
+
+/// A compute pipeline, from one compute `ShaderModule`.
+#[derive(Clone)] pub struct ComputeStyle(Rc<ComputeStyleContext>);
+
+struct ComputeStyleContext {
+	pipeline: VkPipeline,
+	pipeline_layout: VkPipelineLayout,
+	descsetlayout: VkDescriptorSetLayout,
+	descriptors: Vec<Descriptor>,
+	vulkan: Gpu,
+}
+
+impl ComputeStyle {
+	/// Create a compute pipeline for `shader`, which uses `descriptors`
+	/// (bindings numbered in order) in descriptor set 0, and
+	/// `push_constants` bytes of push constants (may be 0).
+	pub fn new(vulkan: &Gpu, shader: &ShaderModule,
+		descriptors: &[Descriptor], push_constants: u32) -> Self
+	{
+		Self::with_cache(vulkan, shader, descriptors, push_constants,
+			None, None)
+	}
+
+	/// Like `new()`, but with the shader's `specialization` constants (if
+	/// any), and reusing (and adding to) compiled pipelines in `cache`.
+	pub fn with_cache(vulkan: &Gpu, shader: &ShaderModule,
+		descriptors: &[Descriptor], push_constants: u32,
+		specialization: Option<&Specialization>,
+		cache: Option<&PipelineCache>) -> Self
+	{ unsafe {
+		assert!(push_constants.is_multiple_of(4) && push_constants
+			<= vulkan.get().properties.limits.max_push_constants_size,
+			"Bad push constants size");
+		if let Some(specialization) = specialization {
+			specialization.check(&vulkan.get().features);
+		}
+
+		let specialization = specialization.map(|s| s.vk());
+
+		let descsetlayout = descriptor::new_layout(vulkan, descriptors,
+			VkShaderStage::Compute);
+
+		let connection = vulkan.get();
+		let mut pipeline = 0;
+		let mut pipeline_layout = 0;
+
+		let push_constant_range = VkPushConstantRange {
+			stage_flags: Stages::COMPUTE.0,
+			offset: 0,
+			size: push_constants,
+		};
+
+		(connection.new_pipeline_layout)(
+			connection.device,
+			&VkPipelineLayoutCreateInfo {
+				s_type: VkStructureType::PipelineLayoutCreateInfo,
+				next: null(),
+				flags: 0,
+				set_layout_count: 1,
+				set_layouts: &descsetlayout,
+				push_constant_range_count: if push_constants == 0 {
+					0
+				} else {
+					1
+				},
+				push_constant_ranges: &push_constant_range,
+			},
+			null(),
+			&mut pipeline_layout
+		).unwrap();
+
+		(connection.new_compute_pipeline)(
+			connection.device,
+			cache.map(|c| c.cache()).unwrap_or(VkPipelineCache(0)),
+			1,
+			&VkComputePipelineCreateInfo {
+				s_type: VkStructureType::ComputePipelineCreateInfo,
+				next: null(),
+				flags: 0,
+				stage: shader.stage_info(Stages::COMPUTE,
+					specialization.as_ref()),
+				layout: pipeline_layout,
+				base_pipeline_handle: mem::zeroed(), // NULL
+				base_pipeline_index: 0,
+			},
+			null(),
+			&mut pipeline
+		).unwrap();
+
+		ComputeStyle(Rc::new(ComputeStyleContext {
+			pipeline, pipeline_layout, descsetlayout,
+			descriptors: descriptors.to_vec(),
+			vulkan: vulkan.clone(),
+		}))
+	} }
+
+	/// Get the `VkPipeline`, for `cmd_bind_compute()`.
+	pub fn pipeline(&self) -> VkPipeline {
+		self.0.pipeline
+	}
+
+	/// Get the `VkPipelineLayout`, for `cmd_bind_compute_descsets()` and
+	/// `cmd_push_constants()`.
+	pub fn pipeline_layout(&self) -> VkPipelineLayout {
+		self.0.pipeline_layout
+	}
+
+	pub(crate) fn descsetlayout(&self) -> VkDescriptorSetLayout {
+		self.0.descsetlayout
+	}
+
+	pub(crate) fn descriptors(&self) -> &[Descriptor] {
+		&self.0.descriptors
+	}
+}
+
+impl Drop for ComputeStyleContext {
+	fn drop(&mut self) {
+		let vk = self.vulkan.get();
+
+		unsafe {
+			(vk.drop_pipeline)(vk.device, self.pipeline, null());
+			(vk.drop_pipeline_layout)(vk.device,
+				self.pipeline_layout, null());
+			(vk.drop_descset_layout)(vk.device, self.descsetlayout,
+				null());
+		}
+	}
+}
+
+/// A dependency between compute and graphics work, for `cmd_barrier()`.
+#[derive(Copy, Clone, Debug, PartialEq)] pub enum Barrier {
+	/// Compute shader writes, read by following compute shaders.
+	ComputeToCompute,
+	/// Compute shader writes, read as vertices, indices or indirect draw
+	/// parameters.
+	ComputeToVertex,
+	/// Compute shader writes, read by graphics shaders.
+	ComputeToShader,
+	/// Graphics shader or attachment writes, read by compute shaders.
+	GraphicsToCompute,
+}
+
+impl Barrier {
+	/// Get the source and destination stages and access masks.
+	pub(crate) fn vk(&self)
+		-> (VkPipelineStage, VkPipelineStage, VkFlags, VkFlags)
+	{
+		let shader_write = VkAccess::ShaderWriteBit as VkFlags;
+		let shader_read = VkAccess::ShaderReadBit as VkFlags;
+
+		match *self {
+			Barrier::ComputeToCompute => (
+				VkPipelineStage::ComputeShader,
+				VkPipelineStage::ComputeShader,
+				shader_write, shader_read,
+			),
+			Barrier::ComputeToVertex => (
+				VkPipelineStage::ComputeShader,
+				VkPipelineStage::DrawIndirectAndVertexInput,
+				shader_write,
+				VkAccess::IndirectCommandReadBit as VkFlags
+					| VkAccess::IndexReadBit as VkFlags
+					| VkAccess::VertexAttributeReadBit
+						as VkFlags,
+			),
+			Barrier::ComputeToShader => (
+				VkPipelineStage::ComputeShader,
+				VkPipelineStage::VertexShaderAndFragmentShader,
+				shader_write,
+				shader_read | VkAccess::UniformReadBit as VkFlags,
+			),
+			Barrier::GraphicsToCompute => (
+				VkPipelineStage::AllGraphics,
+				VkPipelineStage::ComputeShader,
+				shader_write
+					| VkAccess::ColorAttachmentWriteBit
+						as VkFlags
+					| VkAccess::DepthStencilAttachmentWriteBit
+						as VkFlags,
+				shader_read | shader_write,
+			),
+		}
+	}
+}
@@ -0,0 +1,435 @@
+// "asi_vulkan" - Aldaron's System Interface - Vulkan
+//
+// Copyright Jeron A. Lau 2018.
+// Distributed under the Boost Software License, Version 1.0.
+// (See accompanying file LICENSE_1_0.txt or copy at
+// https://www.boost.org/LICENSE_1_0.txt)
+
+use null;
+use mem;
+
+use types::*;
+use Gpu;
+use Image;
+use Buffer;
+use BufferView;
+use ComputeStyle;
+use Style;
+use std::{ rc::Rc };
+
+/// The kind of a descriptor binding.  Bindings are numbered in order.
+#[derive(Copy, Clone, Debug, PartialEq)] pub enum Descriptor {
+	/// A uniform buffer.
+	Uniform,
+	/// A texture, sampled with the `Gpu`'s sampler.
+	Sampler,
+	/// A storage buffer (SSBO).
+	Storage,
+	/// An image that shaders can read and write texels of (`image2D`).
+	StorageImage,
+	/// A `BufferView` of a uniform texel buffer (`samplerBuffer`).
+	UniformTexel,
+	/// A `BufferView` of a storage texel buffer (`imageBuffer`).
+	StorageTexel,
+}
+
+impl Descriptor {
+	pub(crate) fn vk(&self) -> VkDescriptorType {
+		match *self {
+			Descriptor::Uniform => VkDescriptorType::UniformBuffer,
+			Descriptor::Sampler =>
+				VkDescriptorType::CombinedImageSampler,
+			Descriptor::Storage => VkDescriptorType::StorageBuffer,
+			Descriptor::StorageImage =>
+				VkDescriptorType::StorageImage,
+			Descriptor::UniformTexel =>
+				VkDescriptorType::UniformTexelBuffer,
+			Descriptor::StorageTexel =>
+				VkDescriptorType::StorageTexelBuffer,
+		}
+	}
+}
+
+/// What to bind to a `Descriptor`, kept alive as long as the `DescriptorSet`.
+#[derive(Clone)] pub enum Resource {
+	/// A uniform buffer.
+	Uniform(Buffer),
+	/// A texture, in the `General` layout.
+	Sampler(Image),
+	/// A `Storage` (or `StorageVertex`) buffer.
+	Storage(Buffer),
+	/// An image created with `VkImageUsage::StorageSampled`, in the
+	/// `General` layout.
+	StorageImage(Image),
+	/// A view of a `UniformTexel` buffer.
+	UniformTexel(BufferView),
+	/// A view of a `StorageTexel` buffer.
+	StorageTexel(BufferView),
+}
+
+impl Resource {
+	fn descriptor(&self) -> Descriptor {
+		match *self {
+			Resource::Uniform(_) => Descriptor::Uniform,
+			Resource::Sampler(_) => Descriptor::Sampler,
+			Resource::Storage(_) => Descriptor::Storage,
+			Resource::StorageImage(_) => Descriptor::StorageImage,
+			Resource::UniformTexel(_) => Descriptor::UniformTexel,
+			Resource::StorageTexel(_) => Descriptor::StorageTexel,
+		}
+	}
+}
+
+/// A descriptor set for a `ComputeStyle`, or for set 1 of a `Style` (see
+/// `StyleBuilder::descriptors()`), with its own descriptor pool.
+#[derive(Clone)] pub struct DescriptorSet(Rc<DescriptorSetContext>);
+
+struct DescriptorSetContext {
+	desc_set: VkDescriptorSet,
+	desc_pool: VkDescriptorPool,
+	#[allow(unused)] // To keep in scope, preventing segfault.
+	resources: Vec<Resource>,
+	vulkan: Gpu,
+}
+
+impl DescriptorSet {
+	/// Create a descriptor set for `style`, with a `Resource` for each of
+	/// its `Descriptor`s.
+	pub fn new(vulkan: &Gpu, style: &ComputeStyle, resources: Vec<Resource>)
+		-> Self
+	{
+		Self::with_layout(vulkan, style.descriptors(),
+			style.descsetlayout(), resources)
+	}
+
+	/// Create set 1 for `style`, with a `Resource` for each of the
+	/// `Descriptor`s passed to `StyleBuilder::descriptors()`.
+	pub fn for_style(vulkan: &Gpu, style: &Style, resources: Vec<Resource>)
+		-> Self
+	{
+		Self::for_style_set(vulkan, style, 1, resources)
+	}
+
+	/// Create set `set` (0 or 1) for a `style` built with
+	/// `StyleBuilder::reflect()`, with a `Resource` for each binding.
+	pub fn for_style_set(vulkan: &Gpu, style: &Style, set: u32,
+		resources: Vec<Resource>) -> Self
+	{
+		let (descriptors, layout) = style.descriptors(set)
+			.unwrap_or_else(|| {
+				panic!("Style has no descriptor set {}", set)
+			});
+
+		Self::with_layout(vulkan, descriptors, layout, resources)
+	}
+
+	fn with_layout(vulkan: &Gpu, descriptors: &[Descriptor],
+		layout: VkDescriptorSetLayout, resources: Vec<Resource>) -> Self
+	{ unsafe {
+		assert!(resources.iter().map(|r| r.descriptor())
+			.eq(descriptors.iter().cloned()),
+			"Resources don't match the style's descriptors");
+
+		let (desc_pool, desc_set) = new_set(vulkan, descriptors,
+			layout);
+
+		let sampler = vulkan.get().sampler;
+		let mut writer = DescriptorSetWriter::new();
+
+		for resource in resources.iter() {
+			writer = writer.set(match *resource {
+				Resource::Uniform(ref buffer) =>
+					Set::Uniform(desc_set, buffer.buffer()),
+				Resource::Sampler(ref image) =>
+					Set::Sampler(desc_set, sampler,
+						image.view()),
+				Resource::Storage(ref buffer) =>
+					Set::Storage(desc_set, buffer.buffer()),
+				Resource::StorageImage(ref image) =>
+					Set::StorageImage(desc_set,
+						image.view()),
+				Resource::UniformTexel(ref view) =>
+					Set::UniformTexel(desc_set,
+						view.view()),
+				Resource::StorageTexel(ref view) =>
+					Set::StorageTexel(desc_set,
+						view.view()),
+			});
+		}
+
+		let device = vulkan.get().device;
+		writer.update_descriptor_sets(vulkan, device);
+
+		DescriptorSet(Rc::new(DescriptorSetContext {
+			desc_set, desc_pool, resources, vulkan: vulkan.clone(),
+		}))
+	} }
+
+	/// Get the `VkDescriptorSet`, for binding.
+	pub fn set(&self) -> VkDescriptorSet {
+		self.0.desc_set
+	}
+}
+
+impl Drop for DescriptorSetContext {
+	fn drop(&mut self) {
+		let vk = self.vulkan.get();
+
+		unsafe {
+			(vk.drop_descpool)(vk.device, self.desc_pool, null());
+		}
+	}
+}
+
+/// Create a descriptor set layout with `descriptors` numbered in order, used
+/// by `stage`.
+pub(crate) unsafe fn new_layout(vulkan: &Gpu, descriptors: &[Descriptor],
+	stage: VkShaderStage) -> VkDescriptorSetLayout
+{
+	let connection = vulkan.get();
+	let mut descsetlayout = 0;
+
+	let bindings: Vec<VkDescriptorSetLayoutBinding> = descriptors.iter()
+		.enumerate()
+		.map(|(i, descriptor)| VkDescriptorSetLayoutBinding {
+			binding: i as u32,
+			descriptor_type: descriptor.vk(),
+			descriptor_count: 1,
+			stage_flags: stage,
+			immutable_samplers: null(),
+		}).collect();
+
+	(connection.new_descset_layout)(
+		connection.device,
+		&VkDescriptorSetLayoutCreateInfo {
+			s_type: VkStructureType::DescriptorSetLayoutCreateInfo,
+			next: null(),
+			flags: 0,
+			binding_count: bindings.len() as u32,
+			bindings: bindings.as_ptr(),
+		},
+		null(),
+		&mut descsetlayout
+	).unwrap();
+
+	descsetlayout
+}
+
+/// Create a descriptor pool for one set of `layout`, and allocate the set.
+unsafe fn new_set(vulkan: &Gpu, descriptors: &[Descriptor],
+	layout: VkDescriptorSetLayout) -> (VkDescriptorPool, VkDescriptorSet)
+{
+	let connection = vulkan.get();
+	let mut desc_pool = 0;
+	let mut desc_set = 0;
+
+	let pool_sizes: Vec<VkDescriptorPoolSize> = descriptors.iter()
+		.map(|descriptor| VkDescriptorPoolSize {
+			descriptor_type: descriptor.vk(),
+			descriptor_count: 1,
+		}).collect();
+
+	(connection.new_descpool)(
+		connection.device,
+		&VkDescriptorPoolCreateInfo {
+			s_type: VkStructureType::DescriptorPoolCreateInfo,
+			next: null(),
+			flags: 0,
+			max_sets: 1,
+			pool_size_count: pool_sizes.len() as u32,
+			pool_sizes: pool_sizes.as_ptr(),
+		},
+		null(),
+		&mut desc_pool
+	).unwrap();
+
+	(connection.new_descsets)(
+		connection.device,
+		&VkDescriptorSetAllocateInfo {
+			s_type: VkStructureType::DescriptorSetAllocateInfo,
+			next: null(),
+			descriptor_pool: desc_pool,
+			descriptor_set_count: 1,
+			set_layouts: &layout,
+		},
+		&mut desc_set
+	).unwrap();
+
+	(desc_pool, desc_set)
+}
+
+pub(crate) struct DescriptorSetWriter {
+	sets: [Set; 255],
+	nwrites: u8,
+}
+
+impl DescriptorSetWriter {
+	/// Create a new DescriptorSetWriter.
+	#[inline(always)]
+	pub fn new() -> Self {
+		Self {
+			sets: unsafe { mem::uninitialized() },
+			nwrites: 0,
+		}
+	}
+
+	/// Write a uniform buffer to the descriptor set.
+	#[inline(always)]
+	pub fn uniform(mut self, desc_set: VkDescriptorSet, memory: &Buffer)
+		-> Self
+	{
+		self.sets[self.nwrites as usize] = Set::Uniform(desc_set,
+			memory.buffer());
+
+		self.nwrites += 1;
+
+		self
+	}
+
+	/// Write an image sampler to the descriptor set.
+	#[inline(always)]
+	pub fn sampler(mut self, desc_set: VkDescriptorSet,
+		tex_sampler: VkSampler, tex_view: VkImageView) -> Self
+	{
+		self.sets[self.nwrites as usize] = Set::Sampler(desc_set, tex_sampler, tex_view);
+
+		self.nwrites += 1;
+
+		self
+	}
+
+	/// Write any kind of descriptor to the descriptor set.
+	#[inline(always)]
+	pub fn set(mut self, set: Set) -> Self {
+		self.sets[self.nwrites as usize] = set;
+
+		self.nwrites += 1;
+
+		self
+	}
+
+	/// Update the descriptor sets.
+	#[inline(always)]
+	pub fn update_descriptor_sets(&self, connection: &Gpu,
+		device: VkDevice) -> ()
+	{
+		let connection = connection.get();
+
+		let mut buffer_infos: [VkDescriptorBufferInfo; 255] = unsafe {
+			mem::uninitialized()
+		};
+		let mut image_infos: [VkDescriptorImageInfo; 255] = unsafe {
+			mem::uninitialized()
+		};
+		let mut texel_views: [VkBufferView; 255] = [0; 255];
+		let mut writes: [VkWriteDescriptorSet; 255] = unsafe {
+			mem::uninitialized()
+		};
+
+		for i in 0..self.nwrites {
+			match self.sets[i as usize] {
+				Set::Sampler(desc_set, tex_sampler, tex_view) => {
+					image_infos[i as usize] = VkDescriptorImageInfo {
+						sampler: tex_sampler,
+						image_view: tex_view,
+						image_layout: VkImageLayout::General,
+					};
+					writes[i as usize] = VkWriteDescriptorSet {
+						s_type: VkStructureType::WriteDescriptorSet,
+						next: null(),
+						dst_set: desc_set,
+						dst_binding: i as u32,
+						descriptor_count: 1, //tex_count,
+						descriptor_type: VkDescriptorType::CombinedImageSampler,
+						image_info: &image_infos[i as usize],
+						buffer_info: null(),
+						dst_array_element: 0,
+						texel_buffer_view: null(),
+					};
+				}
+				Set::Uniform(desc_set, buffer)
+					| Set::Storage(desc_set, buffer) =>
+				{
+					buffer_infos[i as usize] = VkDescriptorBufferInfo {
+						buffer: buffer,
+						offset: 0,
+						range: !0,
+					};
+					writes[i as usize] = VkWriteDescriptorSet {
+						s_type: VkStructureType::WriteDescriptorSet,
+						next: null(),
+						dst_set: desc_set,
+						dst_binding: i as u32,
+						descriptor_count: 1,
+						descriptor_type: match self.sets[i as usize] {
+							Set::Uniform(..) => VkDescriptorType::UniformBuffer,
+							_ => VkDescriptorType::StorageBuffer,
+						},
+						buffer_info: &buffer_infos[i as usize],
+						dst_array_element: 0,
+						texel_buffer_view: null(),
+						image_info: null(),
+					};
+				}
+				Set::StorageImage(desc_set, view) => {
+					image_infos[i as usize] = VkDescriptorImageInfo {
+						sampler: 0,
+						image_view: view,
+						image_layout: VkImageLayout::General,
+					};
+					writes[i as usize] = VkWriteDescriptorSet {
+						s_type: VkStructureType::WriteDescriptorSet,
+						next: null(),
+						dst_set: desc_set,
+						dst_binding: i as u32,
+						descriptor_count: 1,
+						descriptor_type: VkDescriptorType::StorageImage,
+						image_info: &image_infos[i as usize],
+						buffer_info: null(),
+						dst_array_element: 0,
+						texel_buffer_view: null(),
+					};
+				}
+				Set::UniformTexel(desc_set, view)
+					| Set::StorageTexel(desc_set, view) =>
+				{
+					texel_views[i as usize] = view;
+					writes[i as usize] = VkWriteDescriptorSet {
+						s_type: VkStructureType::WriteDescriptorSet,
+						next: null(),
+						dst_set: desc_set,
+						dst_binding: i as u32,
+						descriptor_count: 1,
+						descriptor_type: match self.sets[i as usize] {
+							Set::UniformTexel(..) => VkDescriptorType::UniformTexelBuffer,
+							_ => VkDescriptorType::StorageTexelBuffer,
+						},
+						image_info: null(),
+						buffer_info: null(),
+						dst_array_element: 0,
+						texel_buffer_view: &texel_views[i as usize],
+					};
+				}
+			}
+		}
+
+		unsafe {
+			(connection.update_descsets)(
+				device,
+				self.nwrites as u32,
+				writes.as_ptr(),
+				0,
+				null(),
+			);
+		}
+	}
+}
+
+pub(crate) enum Set {
+	Uniform(VkDescriptorSet, VkBuffer),
+	Sampler(VkDescriptorSet, VkSampler, VkImageView),
+	Storage(VkDescriptorSet, VkBuffer),
+	StorageImage(VkDescriptorSet, VkImageView),
+	UniformTexel(VkDescriptorSet, VkBufferView),
+	StorageTexel(VkDescriptorSet, VkBufferView),
+}
@@ -15,28 +15,6 @@ pub(super) unsafe fn new(vkd: (VkDevice,
 	pqi: u32)
 	-> Result<(VkCommandBuffer, u64), String>
 {
-	#[repr(C)]
-	enum VkCommandBufferLevel {
-		Primary = 0,
-	}
-
-	#[repr(C)]
-	struct VkCommandPoolCreateInfo {
-		s_type: VkStructureType,
-		p_next: *mut c_void,
-		flags: u32,
-		queue_family_index: u32,
-	}
-
-	#[repr(C)]
-	struct VkCommandBufferAllocateInfo {
-		s_type: VkStructureType,
-		p_next: *mut c_void,
-		command_pool: u64,
-		level: VkCommandBufferLevel,
-		command_buffer_count: u32,
-	}
-
 	// Load function
 	type VkCreateCommandPool = extern "system" fn(device: VkDevice,
 		pCreateInfo: *const VkCommandPoolCreateInfo,
@@ -9,20 +9,25 @@ use gpu;
 use types::*;
 use super::VulkanApi;
 use c_void;
+use VK_SAMPLE_COUNT;
 use std::{ mem, ptr::{ null, null_mut } };
 
+#[repr(C)]
+struct VkQueueFamilyProperties {
+	queue_flags: u32,
+	queue_count: u32,
+	timestamp_valid_bits: u32,
+	min_image_transfer_granularity: VkExtent3D,
+}
+
+type GetGpuQueueFamProps = unsafe extern "system" fn(VkPhysicalDevice,
+	*mut u32, *mut VkQueueFamilyProperties) -> ();
+
+/// Get a GPU that can draw, and present to `surface` if there is one.
 pub(super) unsafe fn get_gpu(vk: VkInstance, lib: &VulkanApi,
-	surface: VkSurfaceKHR)
+	surface: Option<VkSurfaceKHR>)
 	-> Result<(VkPhysicalDevice, u32, bool, VkFormat), String>
 {
-	#[repr(C)]
-	struct VkQueueFamilyProperties {
-		queue_flags: u32,
-		queue_count: u32,
-		timestamp_valid_bits: u32,
-		min_image_transfer_granularity: VkExtent3D,
-	}
-
 	// Load Function
 	type ListGpus = unsafe extern "system" fn(VkInstance, *mut u32,
 		*mut VkPhysicalDevice) -> VkResult;
@@ -42,8 +47,6 @@ pub(super) unsafe fn get_gpu(vk: VkInstance, lib: &VulkanApi,
 	vk_list_gpus(vk, &mut num_gpus, gpus.as_mut_ptr()).unwrap();
 
 	// Load functions
-	type GetGpuQueueFamProps = unsafe extern "system" fn(VkPhysicalDevice,
-		*mut u32, *mut VkQueueFamilyProperties) -> ();
 	type GetGpuSurfaceSupport = unsafe extern "system" fn(VkPhysicalDevice,
 		u32, VkSurfaceKHR, *mut u32) -> VkResult;
 	type GetGpuProps = unsafe extern "system" fn(VkPhysicalDevice, VkFormat,
@@ -77,41 +80,210 @@ pub(super) unsafe fn get_gpu(vk: VkInstance, lib: &VulkanApi,
 
 		for j in 0..queue_families_size {
 			let k = j as u32;
-			let mut supports_present = 0;
 
-			vk_get_support(gpus[i], k, surface,
-				&mut supports_present).unwrap();
+			if (properties[j].queue_flags & 0x00000001) == 0 {
+				continue;
+			}
+
+			let format = if let Some(surface) = surface {
+				let mut supports_present = 0;
+
+				vk_get_support(gpus[i], k, surface,
+					&mut supports_present).unwrap();
+
+				if supports_present == 0 {
+					continue;
+				}
 
-			if supports_present != 0 &&
-				(properties[j].queue_flags & 0x00000001) != 0
-			{
 				// Get format
 				let mut nformats = 1;
 				let mut format = mem::uninitialized();
 				vk_gpu_surface_formats(gpus[i], surface,
 					&mut nformats, &mut format).unwrap();
-				let format = format.format;
+				format.format
+			} else {
+				// Always supported as a color attachment.
+				VkFormat::R8g8b8a8Unorm
+			};
 
-				// 
-				let mut props = mem::uninitialized();
+			// 
+			let mut props = mem::uninitialized();
 
-				vk_gpu_props(gpus[i], format.clone(), &mut props);
+			vk_gpu_props(gpus[i], format.clone(), &mut props);
 
-				return Ok((gpus[i], k,
-					props.linear_tiling_features
-						& 0x00000001 /* sampled image */
-						!= 0,
-					format
-				));
-			}
+			return Ok((gpus[i], k,
+				props.linear_tiling_features
+					& 0x00000001 /* sampled image */
+					!= 0,
+				format
+			));
 		}
 	}
 
 	Err("Couldn't Create Gpu.".to_string())
 }
 
+/// Get the number of valid bits in timestamps written on queue family `pqi`,
+/// 0 if timestamps aren't supported.
+pub(super) unsafe fn get_timestamp_bits(vk: VkInstance, lib: &VulkanApi,
+	gpu: VkPhysicalDevice, pqi: u32) -> Result<u32, String>
+{
+	let vk_get_props: GetGpuQueueFamProps = gpu::vk_sym(vk, lib,
+		b"vkGetPhysicalDeviceQueueFamilyProperties\0")?;
+
+	let mut num_queue_families = 0;
+
+	vk_get_props(gpu, &mut num_queue_families, null_mut());
+
+	let mut properties = Vec::with_capacity(num_queue_families as usize);
+
+	vk_get_props(gpu, &mut num_queue_families, properties.as_mut_ptr());
+	properties.set_len(num_queue_families as usize);
+
+	Ok(properties[pqi as usize].timestamp_valid_bits)
+}
+
+pub(super) unsafe fn get_properties(vk: VkInstance, lib: &VulkanApi,
+	gpu: VkPhysicalDevice) -> Result<VkPhysicalDeviceProperties, String>
+{
+	// Load Function
+	type GetGpuProperties = unsafe extern "system" fn(VkPhysicalDevice,
+		*mut VkPhysicalDeviceProperties) -> ();
+	let vk_get_properties: GetGpuProperties = gpu::vk_sym(vk, lib,
+		b"vkGetPhysicalDeviceProperties\0")?;
+
+	// Run Function
+	let mut properties = mem::zeroed();
+	vk_get_properties(gpu, &mut properties);
+
+	Ok(properties)
+}
+
+/// Get the features `gpu` supports.
+pub(super) unsafe fn get_features(vk: VkInstance, lib: &VulkanApi,
+	gpu: VkPhysicalDevice) -> Result<VkPhysicalDeviceFeatures, String>
+{
+	// Load Function
+	type GetGpuFeatures = unsafe extern "system" fn(VkPhysicalDevice,
+		*mut VkPhysicalDeviceFeatures) -> ();
+	let vk_get_features: GetGpuFeatures = gpu::vk_sym(vk, lib,
+		b"vkGetPhysicalDeviceFeatures\0")?;
+
+	// Run Function
+	let mut features: VkPhysicalDeviceFeatures = mem::zeroed();
+	vk_get_features(gpu, &mut features);
+
+	Ok(features)
+}
+
+/// Get the features to enable out of the `supported` ones: only those that
+/// are used, since some (like robust buffer access) add overhead.
+pub(super) fn enable_features(supported: &VkPhysicalDeviceFeatures)
+	-> VkPhysicalDeviceFeatures
+{
+	let mut features: VkPhysicalDeviceFeatures = unsafe { mem::zeroed() };
+
+	// Viewports & scissors
+	features.multi_viewport = supported.multi_viewport;
+	// Rasterization
+	features.fill_mode_non_solid = supported.fill_mode_non_solid;
+	features.wide_lines = supported.wide_lines;
+	features.depth_bias_clamp = supported.depth_bias_clamp;
+	features.depth_clamp = supported.depth_clamp;
+	// Blending
+	features.independent_blend = supported.independent_blend;
+	features.dual_src_blend = supported.dual_src_blend;
+	features.logic_op = supported.logic_op;
+	// Geometry & tessellation shaders
+	features.geometry_shader = supported.geometry_shader;
+	features.tessellation_shader = supported.tessellation_shader;
+	// 64-bit shader types & specialization constants
+	features.shader_int64 = supported.shader_int64;
+	features.shader_float64 = supported.shader_float64;
+	// Queries
+	features.occlusion_query_precise = supported.occlusion_query_precise;
+	features.pipeline_statistics_query =
+		supported.pipeline_statistics_query;
+	// Storage buffers & images written outside of compute shaders
+	features.vertex_pipeline_stores_and_atomics =
+		supported.vertex_pipeline_stores_and_atomics;
+	features.fragment_stores_and_atomics =
+		supported.fragment_stores_and_atomics;
+	// Indirect draws
+	features.multi_draw_indirect = supported.multi_draw_indirect;
+	features.draw_indirect_first_instance =
+		supported.draw_indirect_first_instance;
+
+	// Shader capabilities, for shaders compiled at runtime
+	features.image_cube_array = supported.image_cube_array;
+	features.sample_rate_shading = supported.sample_rate_shading;
+	features.shader_clip_distance = supported.shader_clip_distance;
+	features.shader_cull_distance = supported.shader_cull_distance;
+	features.shader_storage_image_extended_formats =
+		supported.shader_storage_image_extended_formats;
+
+	features
+}
+
+/// Get the highest sample count up to `VK_SAMPLE_COUNT` that both color and
+/// depth attachments support (software implementations often only do 4).
+pub(super) fn get_sample_count(properties: &VkPhysicalDeviceProperties)
+	-> VkSampleCount
+{
+	let supported = properties.limits.framebuffer_color_sample_counts
+		& properties.limits.framebuffer_depth_sample_counts;
+
+	for count in [VkSampleCount::Sc64, VkSampleCount::Sc32,
+		VkSampleCount::Sc16, VkSampleCount::Sc8, VkSampleCount::Sc4,
+		VkSampleCount::Sc2].iter()
+	{
+		let count = *count;
+
+		if count as u32 <= VK_SAMPLE_COUNT as u32
+			&& supported & count as u32 != 0
+		{
+			return count;
+		}
+	}
+
+	VkSampleCount::Sc1
+}
+
+/// Get whether or not `gpu` supports device extension `name`.
+pub(super) unsafe fn has_extension(vk: VkInstance, lib: &VulkanApi,
+	gpu: VkPhysicalDevice, name: &[u8]) -> Result<bool, String>
+{
+	#[repr(C)]
+	struct VkExtensionProperties {
+		extension_name: [u8; 256],
+		spec_version: u32,
+	}
+
+	// Load Function
+	type ListExtensions = unsafe extern "system" fn(VkPhysicalDevice,
+		*const i8, *mut u32, *mut VkExtensionProperties) -> VkResult;
+	let vk_list_extensions: ListExtensions = gpu::vk_sym(vk, lib,
+		b"vkEnumerateDeviceExtensionProperties\0")?;
+
+	// Run Function
+	let mut nextensions = 0;
+	vk_list_extensions(gpu, null(), &mut nextensions, null_mut())
+		.unwrap();
+	let mut extensions = Vec::with_capacity(nextensions as usize);
+	vk_list_extensions(gpu, null(), &mut nextensions,
+		extensions.as_mut_ptr()).unwrap();
+	extensions.set_len(nextensions as usize);
+
+	Ok(extensions.iter().any(|extension: &VkExtensionProperties| {
+		extension.extension_name.starts_with(name)
+	}))
+}
+
+/// Create the device, enabling `features` and the swapchain extension along
+/// with any in `extensions` (nul-terminated).
 pub(super) unsafe fn create_device(vk: VkInstance, lib: &VulkanApi,
-	gpu: VkPhysicalDevice, pqi: u32) -> VkDevice
+	gpu: VkPhysicalDevice, pqi: u32, features: &VkPhysicalDeviceFeatures,
+	extensions: &[&[u8]]) -> VkDevice
 {
 	let mut device = mem::uninitialized();
 
@@ -136,7 +308,7 @@ pub(super) unsafe fn create_device(vk: VkInstance, lib: &VulkanApi,
 		enabled_layer_names: *const *const u8,
 		enabled_extension_count: u32,
 		enabled_extension_names: *const *const u8,
-		enabled_features: *mut c_void,
+		enabled_features: *const VkPhysicalDeviceFeatures,
 	}
 
 	// Load function
@@ -148,7 +320,8 @@ pub(super) unsafe fn create_device(vk: VkInstance, lib: &VulkanApi,
 	let vk_create_device: VkCreateDevice = gpu::vk_sym(vk, lib,
 		b"vkCreateDevice\0").unwrap();
 
-	let ext = b"VK_KHR_swapchain\0";
+	let mut ext = vec![b"VK_KHR_swapchain\0".as_ptr()];
+	ext.extend(extensions.iter().map(|extension| extension.as_ptr()));
 
 	vk_create_device(gpu, &VkDeviceCreateInfo {
 		s_type: VkStructureType::DeviceCreateInfo,
@@ -165,9 +338,9 @@ pub(super) unsafe fn create_device(vk: VkInstance, lib: &VulkanApi,
 		}].as_ptr(),
 		enabled_layer_count: 0,
 		enabled_layer_names: null(),
-		enabled_extension_count: 1,
-		enabled_extension_names: [ext.as_ptr()].as_ptr(),
-		enabled_features: null_mut(),
+		enabled_extension_count: ext.len() as u32,
+		enabled_extension_names: ext.as_ptr(),
+		enabled_features: features,
 	}, null_mut(), &mut device).unwrap();
 
 	device
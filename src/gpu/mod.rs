@@ -33,6 +33,9 @@ const DL: &'static str = "libvulkan.so.1";
 #[cfg(target_os = "macos")]
 const DL: &'static str = "libMoltenVK.dylib";
 
+// Device extension enabled if supported, for draws with a GPU-written count.
+const DRAW_INDIRECT_COUNT: &'static [u8] = b"VK_KHR_draw_indirect_count\0";
+
 #[inline(always)]
 pub(crate) unsafe fn vk_sym<T>(vk: VkInstance, lib: &VulkanApi, name: &[u8])
 	-> Result<T, String>
@@ -145,10 +148,20 @@ pub(crate) struct GpuContext {
 	pub(crate) gpu: VkPhysicalDevice,
 	pub(crate) swapchain: VkSwapchainKHR,
 	pub(crate) sampled: bool,
+	pub(crate) readable: bool,
+	// Swapchain render passes that load some attachments instead of
+	// clearing them, created when first needed.
+	pub(crate) swapchain_load_passes: RefCell<Vec<(Vec<bool>, VkRenderPass)>>,
+	pub(crate) properties: VkPhysicalDeviceProperties,
+	// The enabled features, which are supported.
+	pub(crate) features: VkPhysicalDeviceFeatures,
+	pub(crate) sample_count: VkSampleCount,
 	pub(crate) device: VkDevice,
 	pub(crate) present_queue: VkQueue,
 	pub(crate) command_buffer: VkCommandBuffer,
 	pub(crate) command_pool: u64,
+	pub(crate) queue_family: u32,
+	pub(crate) timestamp_bits: u32,
 	pub(crate) sampler: VkSampler,
 	pub(crate) rgb: Vec3,
 	pub(crate) api: VulkanApi,
@@ -159,6 +172,25 @@ pub(crate) struct GpuContext {
 		-> VkResult,
 	pub(crate) draw: unsafe extern "system" fn(VkCommandBuffer, u32, u32, u32, u32)
 		-> (),
+	pub(crate) draw_indexed: unsafe extern "system" fn(VkCommandBuffer, u32,
+		u32, u32, i32, u32) -> (),
+	pub(crate) dispatch: unsafe extern "system" fn(VkCommandBuffer, u32, u32,
+		u32) -> (),
+	pub(crate) dispatch_indirect: unsafe extern "system" fn(VkCommandBuffer,
+		VkBuffer, VkDeviceSize) -> (),
+	pub(crate) bind_ib: unsafe extern "system" fn(VkCommandBuffer, VkBuffer,
+		VkDeviceSize, VkIndexType) -> (),
+	pub(crate) draw_indirect: unsafe extern "system" fn(VkCommandBuffer,
+		VkBuffer, VkDeviceSize, u32, u32) -> (),
+	pub(crate) draw_indexed_indirect: unsafe extern "system" fn(
+		VkCommandBuffer, VkBuffer, VkDeviceSize, u32, u32) -> (),
+	// From VK_KHR_draw_indirect_count, if supported.
+	pub(crate) draw_indirect_count: Option<unsafe extern "system" fn(
+		VkCommandBuffer, VkBuffer, VkDeviceSize, VkBuffer, VkDeviceSize,
+		u32, u32) -> ()>,
+	pub(crate) draw_indexed_indirect_count: Option<unsafe extern "system" fn(
+		VkCommandBuffer, VkBuffer, VkDeviceSize, VkBuffer, VkDeviceSize,
+		u32, u32) -> ()>,
 	pub(crate) unmap: unsafe extern "system" fn(VkDevice, VkDeviceMemory) -> (),
 	pub(crate) new_swapchain: unsafe extern "system" fn(VkDevice,
 		*const VkSwapchainCreateInfoKHR, *const c_void,
@@ -170,6 +202,22 @@ pub(crate) struct GpuContext {
 		-> VkResult,
 	pub(crate) begin_cmdbuff: unsafe extern "system" fn(VkCommandBuffer,
 		*const VkCommandBufferBeginInfo) -> VkResult,
+	pub(crate) new_querypool: unsafe extern "system" fn(VkDevice,
+		*const VkQueryPoolCreateInfo, *const c_void, *mut VkQueryPool)
+		-> VkResult,
+	pub(crate) drop_querypool: unsafe extern "system" fn(VkDevice,
+		VkQueryPool, *const c_void) -> (),
+	pub(crate) reset_queries: unsafe extern "system" fn(VkCommandBuffer,
+		VkQueryPool, u32, u32) -> (),
+	pub(crate) begin_query: unsafe extern "system" fn(VkCommandBuffer,
+		VkQueryPool, u32, VkFlags) -> (),
+	pub(crate) end_query: unsafe extern "system" fn(VkCommandBuffer,
+		VkQueryPool, u32) -> (),
+	pub(crate) write_timestamp: unsafe extern "system" fn(VkCommandBuffer,
+		VkPipelineStage, VkQueryPool, u32) -> (),
+	pub(crate) query_results: unsafe extern "system" fn(VkDevice,
+		VkQueryPool, u32, u32, usize, *mut c_void, VkDeviceSize, VkFlags)
+		-> VkResult,
 	pub(crate) pipeline_barrier: unsafe extern "system" fn(VkCommandBuffer,
 		VkPipelineStage, VkPipelineStage, VkFlags, u32,
 		*const VkMemoryBarrier, u32, *const VkBufferMemoryBarrier, u32,
@@ -182,6 +230,16 @@ pub(crate) struct GpuContext {
 	pub(crate) reset_fence: unsafe extern "system" fn(VkDevice, u32, *const VkFence)
 		-> VkResult,
 	pub(crate) reset_cmdbuff: unsafe extern "system" fn(VkCommandBuffer, VkFlags),
+	pub(crate) new_cmdpool: unsafe extern "system" fn(VkDevice,
+		*const VkCommandPoolCreateInfo, *const c_void, *mut u64)
+		-> VkResult,
+	pub(crate) drop_cmdpool: unsafe extern "system" fn(VkDevice, u64,
+		*const c_void) -> (),
+	pub(crate) new_cmdbuffs: unsafe extern "system" fn(VkDevice,
+		*const VkCommandBufferAllocateInfo, *mut VkCommandBuffer)
+		-> VkResult,
+	pub(crate) drop_cmdbuffs: unsafe extern "system" fn(VkDevice, u64, u32,
+		*const VkCommandBuffer) -> (),
 	pub(crate) create_imgview: unsafe extern "system" fn(VkDevice,
 		*const VkImageViewCreateInfo, *const c_void, *mut VkImageView)
 		-> VkResult,
@@ -228,6 +286,11 @@ pub(crate) struct GpuContext {
 	pub(crate) new_buffer: unsafe extern "system" fn(VkDevice,
 		*const VkBufferCreateInfo, *const c_void, *mut VkBuffer)
 		-> VkResult,
+	pub(crate) new_bufview: unsafe extern "system" fn(VkDevice,
+		*const VkBufferViewCreateInfo, *const c_void, *mut VkBufferView)
+		-> VkResult,
+	pub(crate) drop_bufview: unsafe extern "system" fn(VkDevice,
+		VkBufferView, *const c_void) -> (),
 	pub(crate) new_descpool: unsafe extern "system" fn(VkDevice,
 		*const VkDescriptorPoolCreateInfo, *const c_void,
 		*mut VkDescriptorPool) -> VkResult,
@@ -239,9 +302,19 @@ pub(crate) struct GpuContext {
 		*mut VkShaderModule) -> VkResult,
 	pub(crate) drop_shademod: unsafe extern "system" fn(VkDevice, VkShaderModule,
 		*const c_void) -> (),
+	pub(crate) new_pipeline_cache: unsafe extern "system" fn(VkDevice,
+		*const VkPipelineCacheCreateInfo, *const c_void,
+		*mut VkPipelineCache) -> VkResult,
+	pub(crate) drop_pipeline_cache: unsafe extern "system" fn(VkDevice,
+		VkPipelineCache, *const c_void) -> (),
+	pub(crate) pipeline_cache_data: unsafe extern "system" fn(VkDevice,
+		VkPipelineCache, *mut usize, *mut c_void) -> VkResult,
 	pub(crate) new_pipeline: unsafe extern "system" fn(VkDevice, VkPipelineCache, u32,
 		*const VkGraphicsPipelineCreateInfo, *const c_void,
 		*mut VkPipeline) -> VkResult,
+	pub(crate) new_compute_pipeline: unsafe extern "system" fn(VkDevice,
+		VkPipelineCache, u32, *const VkComputePipelineCreateInfo,
+		*const c_void, *mut VkPipeline) -> VkResult,
 	pub(crate) drop_pipeline: unsafe extern "system" fn(VkDevice,
 		VkPipeline, *const c_void) -> (),
 	pub(crate) new_pipeline_layout: unsafe extern "system" fn(VkDevice,
@@ -261,6 +334,8 @@ pub(crate) struct GpuContext {
 	pub(crate) bind_descsets: unsafe extern "system" fn(VkCommandBuffer,
 		VkPipelineBindPoint, VkPipelineLayout, u32, u32,
 		*const VkDescriptorSet, u32, *const u32) -> (),
+	pub(crate) push_constants: unsafe extern "system" fn(VkCommandBuffer,
+		VkPipelineLayout, VkFlags, u32, u32, *const c_void) -> (),
 	pub(crate) new_semaphore: unsafe extern "system" fn(VkDevice,
 		*const VkSemaphoreCreateInfo, *const c_void, *mut VkSemaphore)
 		-> VkResult,
@@ -271,6 +346,9 @@ pub(crate) struct GpuContext {
 	pub(crate) copy_image: unsafe extern "system" fn(VkCommandBuffer, VkImage,
 		VkImageLayout, VkImage, VkImageLayout, u32, *const VkImageCopy)
 		-> (),
+	pub(crate) copy_image_to_buffer: unsafe extern "system" fn(
+		VkCommandBuffer, VkImage, VkImageLayout, VkBuffer, u32,
+		*const VkBufferImageCopy) -> (),
 	pub(crate) subres_layout: unsafe extern "system" fn(VkDevice, VkImage,
 		*const VkImageSubresource, *mut VkSubresourceLayout) -> (),
 	pub(crate) get_surface_capabilities: unsafe extern "system" fn(VkPhysicalDevice,
@@ -281,7 +359,13 @@ pub(crate) struct GpuContext {
 		*const VkViewport) -> (),
 	pub(crate) set_scissor: unsafe extern "system" fn(VkCommandBuffer, u32, u32,
 		*const VkRect2D) -> (),
+	pub(crate) set_blend_constants: unsafe extern "system" fn(
+		VkCommandBuffer, *const [f32; 4]) -> (),
 	pub(crate) end_render_pass: unsafe extern "system" fn(VkCommandBuffer) -> (),
+	pub(crate) execute_commands: unsafe extern "system" fn(VkCommandBuffer,
+		u32, *const VkCommandBuffer) -> (),
+	pub(crate) clear_attachments: unsafe extern "system" fn(VkCommandBuffer,
+		u32, *const VkClearAttachment, u32, *const VkClearRect) -> (),
 	pub(crate) destroy_fence: unsafe extern "system" fn(VkDevice, VkFence, *const c_void)
 		-> (),
 	pub(crate) queue_present: unsafe extern "system" fn(VkQueue, *const VkPresentInfo) -> VkResult,
@@ -305,8 +389,51 @@ impl Gpu {
 		let window = awi::Window::new(window.0, window.1, None);
 		let surface = surface::new(vk, &api, window.get_connection());
 		let (gpu, pqi, sampled, format)
-			= device::get_gpu(vk, &api, surface)?;
-		let device = device::create_device(vk, &api, gpu, pqi);
+			= device::get_gpu(vk, &api, Some(surface))?;
+
+		Ok((Gpu::with_gpu(api, vk, surface, gpu, pqi, sampled, format,
+			rgb)?, window))
+	} }
+
+	/// Create the GPU context without a window or surface, for rendering
+	/// only to `RenderTarget`s (for example, in tests on a software
+	/// Vulkan implementation).
+	pub fn new_headless(rgb: Vec3) -> Result<Gpu, String> { unsafe {
+		// Load the Vulkan library
+		let api = VulkanApi::new()?;
+
+		let vk = create_instance(
+			vk_sym(mem::zeroed(), &api, b"vkCreateInstance\0")?
+		);
+
+		// Null surface.
+		let surface = 0;
+		let (gpu, pqi, sampled, format)
+			= device::get_gpu(vk, &api, None)?;
+
+		Gpu::with_gpu(api, vk, surface, gpu, pqi, sampled, format, rgb)
+	} }
+
+	unsafe fn with_gpu(api: VulkanApi, vk: VkInstance,
+		surface: VkSurfaceKHR, gpu: VkPhysicalDevice, pqi: u32,
+		sampled: bool, format: VkFormat, rgb: Vec3)
+		-> Result<Gpu, String>
+	{
+		let features = device::enable_features(
+			&device::get_features(vk, &api, gpu)?);
+		let draw_indirect_count = device::has_extension(vk, &api, gpu,
+			DRAW_INDIRECT_COUNT)?;
+		let extensions: &[&[u8]] = if draw_indirect_count {
+			&[DRAW_INDIRECT_COUNT]
+		} else {
+			&[]
+		};
+		let device = device::create_device(vk, &api, gpu, pqi, &features,
+			extensions);
+		let properties = device::get_properties(vk, &api, gpu)?;
+		let timestamp_bits = device::get_timestamp_bits(vk, &api, gpu,
+			pqi)?;
+		let sample_count = device::get_sample_count(&properties);
 		// Null swapchain.
 		let swapchain = 0;
 		// Device's loader
@@ -321,25 +448,57 @@ impl Gpu {
 		// Finish connection with the texture sampler.
 		let sampler = sampler::new((device, vkdsym))?;
 		let extent = VkExtent2D { width: 0, height: 0 };
+		// Set when the swapchain is created.
+		let readable = false;
 
-		Ok((Gpu(Rc::new(RefCell::new(GpuContext {
+		Ok(Gpu(Rc::new(RefCell::new(GpuContext {
 			vk, surface, gpu, sampled, device, rgb, swapchain,
 			present_queue, command_buffer, command_pool, sampler,
-			format, extent,
+			queue_family: pqi, timestamp_bits,
+			format, extent, readable, properties, features,
+			sample_count,
+			swapchain_load_passes: RefCell::new(vec![]),
 			// TODO: use vkd_sym.
 			mapmem: vkd_sym(device, vkdsym, b"vkMapMemory\0")?,
 			draw: vkd_sym(device, vkdsym, b"vkCmdDraw\0")?,
+			draw_indexed: vkd_sym(device, vkdsym, b"vkCmdDrawIndexed\0")?,
+			dispatch: vkd_sym(device, vkdsym, b"vkCmdDispatch\0")?,
+			dispatch_indirect: vkd_sym(device, vkdsym, b"vkCmdDispatchIndirect\0")?,
+			draw_indirect: vkd_sym(device, vkdsym, b"vkCmdDrawIndirect\0")?,
+			draw_indexed_indirect: vkd_sym(device, vkdsym, b"vkCmdDrawIndexedIndirect\0")?,
+			draw_indirect_count: if draw_indirect_count {
+				Some(vkd_sym(device, vkdsym, b"vkCmdDrawIndirectCountKHR\0")?)
+			} else {
+				None
+			},
+			draw_indexed_indirect_count: if draw_indirect_count {
+				Some(vkd_sym(device, vkdsym, b"vkCmdDrawIndexedIndirectCountKHR\0")?)
+			} else {
+				None
+			},
+			bind_ib: vkd_sym(device, vkdsym, b"vkCmdBindIndexBuffer\0")?,
 			unmap: vkd_sym(device, vkdsym, b"vkUnmapMemory\0")?,
 			new_swapchain: vkd_sym(device, vkdsym, b"vkCreateSwapchainKHR\0")?,
 			get_swapcount: vkd_sym(device, vkdsym, b"vkGetSwapchainImagesKHR\0")?,
 			create_fence: vkd_sym(device, vkdsym, b"vkCreateFence\0")?,
 			begin_cmdbuff: vkd_sym(device, vkdsym, b"vkBeginCommandBuffer\0")?,
+			new_querypool: vkd_sym(device, vkdsym, b"vkCreateQueryPool\0")?,
+			drop_querypool: vkd_sym(device, vkdsym, b"vkDestroyQueryPool\0")?,
+			reset_queries: vkd_sym(device, vkdsym, b"vkCmdResetQueryPool\0")?,
+			begin_query: vkd_sym(device, vkdsym, b"vkCmdBeginQuery\0")?,
+			end_query: vkd_sym(device, vkdsym, b"vkCmdEndQuery\0")?,
+			write_timestamp: vkd_sym(device, vkdsym, b"vkCmdWriteTimestamp\0")?,
+			query_results: vkd_sym(device, vkdsym, b"vkGetQueryPoolResults\0")?,
 			pipeline_barrier: vkd_sym(device, vkdsym, b"vkCmdPipelineBarrier\0")?,
 			end_cmdbuff: vkd_sym(device, vkdsym, b"vkEndCommandBuffer\0")?,
 			queue_submit: vkd_sym(device, vkdsym, b"vkQueueSubmit\0")?,
 			wait_fence: vkd_sym(device, vkdsym, b"vkWaitForFences\0")?,
 			reset_fence: vkd_sym(device, vkdsym, b"vkResetFences\0")?,
 			reset_cmdbuff: vkd_sym(device, vkdsym, b"vkResetCommandBuffer\0")?,
+			new_cmdpool: vkd_sym(device, vkdsym, b"vkCreateCommandPool\0")?,
+			drop_cmdpool: vkd_sym(device, vkdsym, b"vkDestroyCommandPool\0")?,
+			new_cmdbuffs: vkd_sym(device, vkdsym, b"vkAllocateCommandBuffers\0")?,
+			drop_cmdbuffs: vkd_sym(device, vkdsym, b"vkFreeCommandBuffers\0")?,
 			create_imgview: vkd_sym(device, vkdsym, b"vkCreateImageView\0")?,
 			get_memprops: vk_sym(vk, &api,
 				b"vkGetPhysicalDeviceMemoryProperties\0")?,
@@ -363,11 +522,17 @@ impl Gpu {
 			get_bufmemreq: vk_sym(vk, &api,
 				b"vkGetBufferMemoryRequirements\0")?,
 			new_buffer: vkd_sym(device, vkdsym, b"vkCreateBuffer\0")?,
+			new_bufview: vkd_sym(device, vkdsym, b"vkCreateBufferView\0")?,
+			drop_bufview: vkd_sym(device, vkdsym, b"vkDestroyBufferView\0")?,
 			new_descpool: vkd_sym(device, vkdsym, b"vkCreateDescriptorPool\0")?,
 			new_descsets: vkd_sym(device, vkdsym, b"vkAllocateDescriptorSets\0")?,
 			new_shademod: vkd_sym(device, vkdsym, b"vkCreateShaderModule\0")?,
 			drop_shademod: vkd_sym(device, vkdsym, b"vkDestroyShaderModule\0")?,
+			new_pipeline_cache: vkd_sym(device, vkdsym, b"vkCreatePipelineCache\0")?,
+			drop_pipeline_cache: vkd_sym(device, vkdsym, b"vkDestroyPipelineCache\0")?,
+			pipeline_cache_data: vkd_sym(device, vkdsym, b"vkGetPipelineCacheData\0")?,
 			new_pipeline: vkd_sym(device, vkdsym, b"vkCreateGraphicsPipelines\0")?,
+			new_compute_pipeline: vkd_sym(device, vkdsym, b"vkCreateComputePipelines\0")?,
 			drop_pipeline: vkd_sym(device, vkdsym, b"vkDestroyPipeline\0")?,
 			new_pipeline_layout:
 				vkd_sym(device, vkdsym, b"vkCreatePipelineLayout\0")?,
@@ -380,10 +545,13 @@ impl Gpu {
 			bind_vb: vkd_sym(device, vkdsym, b"vkCmdBindVertexBuffers\0")?,
 			bind_pipeline: vkd_sym(device, vkdsym, b"vkCmdBindPipeline\0")?,
 			bind_descsets: vkd_sym(device, vkdsym, b"vkCmdBindDescriptorSets\0")?,
+			push_constants: vkd_sym(device, vkdsym, b"vkCmdPushConstants\0")?,
 			new_semaphore: vkd_sym(device, vkdsym, b"vkCreateSemaphore\0")?,
 			drop_semaphore: vkd_sym(device, vkdsym, b"vkDestroySemaphore\0")?,
 			get_next_image: vkd_sym(device, vkdsym, b"vkAcquireNextImageKHR\0")?,
 			copy_image: vkd_sym(device, vkdsym, b"vkCmdCopyImage\0")?,
+			copy_image_to_buffer: vkd_sym(device, vkdsym,
+				b"vkCmdCopyImageToBuffer\0")?,
 			subres_layout:
 				vkd_sym(device, vkdsym, b"vkGetImageSubresourceLayout\0")?,
 			get_surface_capabilities: vk_sym(vk, &api,
@@ -391,13 +559,17 @@ impl Gpu {
 			begin_render: vkd_sym(device, vkdsym, b"vkCmdBeginRenderPass\0")?,
 			set_viewport: vkd_sym(device, vkdsym, b"vkCmdSetViewport\0")?,
 			set_scissor: vkd_sym(device, vkdsym, b"vkCmdSetScissor\0")?,
+			set_blend_constants: vkd_sym(device, vkdsym, b"vkCmdSetBlendConstants\0")?,
 			end_render_pass: vkd_sym(device, vkdsym, b"vkCmdEndRenderPass\0")?,
+			execute_commands: vkd_sym(device, vkdsym, b"vkCmdExecuteCommands\0")?,
+			clear_attachments:
+				vkd_sym(device, vkdsym, b"vkCmdClearAttachments\0")?,
 			destroy_fence: vkd_sym(device, vkdsym, b"vkDestroyFence\0")?,
 			queue_present: vkd_sym(device, vkdsym, b"vkQueuePresentKHR\0")?,
 			wait_idle: vkd_sym(device, vkdsym, b"vkDeviceWaitIdle\0")?,
 			api,
-		}))), window))
-	} }
+		}))))
+	}
 
 	/// Set the clear color.
 	pub fn color(&self, rgb: Vec3) {
@@ -422,6 +594,48 @@ impl Gpu {
 		self.get().sampled
 	}
 
+	/// Get the name of the GPU (or software implementation) in use.
+	pub fn name(&self) -> String {
+		let name = &self.get().properties.device_name;
+		let len = name.iter().position(|c| *c == 0).unwrap_or(name.len());
+
+		name[..len].iter().map(|c| *c as u8 as char).collect()
+	}
+
+	/// Whether or not swapchain images can be read back to the CPU.
+	pub fn readable(&self) -> bool {
+		self.get().readable
+	}
+
+	/// Get the number of viewports that may be set at once, 1 if the GPU
+	/// doesn't support multiple viewports.
+	pub fn max_viewports(&self) -> u32 {
+		self.get().max_viewports()
+	}
+
+	/// Whether or not `cmd_draw_indirect_count()` and
+	/// `cmd_draw_indexed_indirect_count()` are supported.
+	pub fn draw_indirect_count(&self) -> bool {
+		self.get().draw_indirect_count.is_some()
+	}
+
+	/// Whether or not `QueryKind::Timestamp` queries are supported.
+	pub fn timestamps(&self) -> bool {
+		self.get().timestamp_bits != 0
+	}
+
+	/// Whether or not `QueryKind::PipelineStatistics` queries are
+	/// supported.
+	pub fn pipeline_statistics(&self) -> bool {
+		self.get().features.pipeline_statistics_query != 0
+	}
+
+	/// Whether or not precise occlusion queries (actual sample counts,
+	/// rather than just zero or not zero) are supported.
+	pub fn precise_occlusion(&self) -> bool {
+		self.get().features.occlusion_query_precise != 0
+	}
+
 	/// Aspect ratio
 	pub fn ar(&self) -> f32 {
 		let connection = self.get();
@@ -430,6 +644,16 @@ impl Gpu {
 	}
 }
 
+impl GpuContext {
+	pub(crate) fn max_viewports(&self) -> u32 {
+		if self.features.multi_viewport == 0 {
+			1
+		} else {
+			self.properties.limits.max_viewports
+		}
+	}
+}
+
 impl Drop for GpuContext {
 	fn drop(&mut self) -> () {
 		// Load Function (Sampler)
@@ -460,8 +684,18 @@ impl Drop for GpuContext {
 			sym(self, b"vkDestroySurfaceKHR\0").unwrap()
 		};
 
-		// Run Function (Surface)
-		unsafe { destroy(self.vk, self.surface, null_mut()) }
+		// Run Function (Surface), unless headless
+		if self.surface != 0 {
+			unsafe { destroy(self.vk, self.surface, null_mut()) }
+		}
+
+		// Swapchain render passes not freed by destroy_swapchain().
+		for &(_, render_pass) in self.swapchain_load_passes.borrow().iter() {
+			unsafe {
+				(self.drop_renderpass)(self.device, render_pass,
+					null());
+			}
+		}
 
 		// Load Function
 		type VkDestroyDevice = unsafe extern "system" fn(VkDevice,
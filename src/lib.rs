@@ -9,6 +9,8 @@
 extern crate dl_api;
 extern crate awi;
 extern crate euler;
+#[cfg(feature = "compiler")]
+extern crate naga;
 
 // Modules
 pub mod types;
@@ -18,19 +20,75 @@ mod image;
 mod gpu;
 mod sprite;
 mod style;
+mod render_target;
+mod readback;
+mod clear;
+mod viewport;
+mod raster;
+mod blend;
+mod vertex;
+mod command;
+mod stages;
+mod descriptor;
+mod compute;
+mod query;
+mod pipeline_cache;
+mod reflect;
+mod specialization;
+#[cfg(feature = "compiler")]
+mod compiler;
 pub mod fence;
 
 //
 use std::{ mem, u64 };
+use std::convert::TryFrom;
 use std::ptr::{ null, null_mut };
 use std::os::raw::c_void;
+use std::ffi::CString;
+use std::rc::Rc;
 
 // Export Types
-pub use self::memory::{ Memory, Buffer, BufferBuilderType };
+pub use self::memory::{
+	Memory, Buffer, BufferBuilderType, BufferView, IndexType
+};
 pub use self::image::Image;
 pub use self::sprite::Sprite;
-pub use self::style::Style;
+pub use self::style::{ Style, StyleBuilder };
+pub use self::render_target::RenderTarget;
+pub use self::readback::{
+	readback_swapchain, readback_target, write_ppm, write_png
+};
 pub use self::fence::Fence;
+pub use self::clear::Clear;
+pub use self::viewport::{ Viewport, Scissor };
+pub use self::vertex::{
+	VertexFormat, InputRate, Vertex, VertexLayout
+};
+pub use self::blend::{
+	BlendFactor, BlendOp, Blend, ColorMask, Attachment, LogicOp
+};
+pub use self::raster::{
+	Topology, CullMode, FrontFace, PolygonMode, CompareOp, DepthBias
+};
+pub use self::stages::Stages;
+pub use self::descriptor::{ Descriptor, Resource, DescriptorSet };
+pub use self::compute::{ ComputeStyle, Barrier };
+pub use self::pipeline_cache::PipelineCache;
+#[cfg(feature = "compiler")]
+pub use self::compiler::{ Language, CompileError, compile };
+pub use self::specialization::{ Constant, Constants, Specialization };
+pub use self::reflect::{
+	Reflection, EntryPoint, Interface, Type, Scalar, DescriptorBinding,
+	interface_mismatches,
+};
+pub use self::query::{
+	QueryKind, QueryPool, PipelineStatistics, Profiler, GpuZone, ZoneTime
+};
+pub use self::command::{
+	CommandPool, CommandBuffer, Recorder, RenderPassRecorder,
+	SecondaryRenderPass, SecondaryPool, SecondaryCommandBuffer,
+	SecondaryRecorder
+};
 pub use self::gpu::Gpu;
 pub use euler::Vec3;
 
@@ -40,6 +98,7 @@ use self::types::*;
 const VK_MEMORY_PROPERTY_HOST_VISIBLE_BIT: VkFlags = 0x00000002;
 const VK_MEMORY_PROPERTY_HOST_COHERENT_BIT: VkFlags = 0x00000004;
 
+// The most samples to use for multisampling, if supported.
 const VK_SAMPLE_COUNT: VkSampleCount = VkSampleCount::Sc8;
 
 // TODO
@@ -53,6 +112,24 @@ const VK_SAMPLE_COUNT: VkSampleCount = VkSampleCount::Sc8;
 	pub fogr: [f32; 2],
 }
 
+/// The parameters of one draw in an indirect buffer, like `cmd_draw()`.
+#[derive(Copy, Clone, Debug)] #[repr(C)] pub struct DrawIndirectCommand {
+	pub nvertices: u32,
+	pub ninstances: u32,
+	pub firstvertex: u32,
+	pub firstinstance: u32,
+}
+
+/// The parameters of one draw in an indirect buffer, like
+/// `cmd_draw_indexed()`.
+#[derive(Copy, Clone, Debug)] #[repr(C)] pub struct DrawIndexedIndirectCommand {
+	pub nindices: u32,
+	pub ninstances: u32,
+	pub firstindex: u32,
+	pub vertexoffset: i32,
+	pub firstinstance: u32,
+}
+
 pub unsafe fn queue_present(connection: &Gpu, next: u32) {
 	let connection = connection.get();
 
@@ -160,6 +237,30 @@ pub unsafe fn cmd_bind_descsets(connection: &Gpu,
 	);
 }
 
+/// Bind descriptor set `first_set` (1 for sets from
+/// `DescriptorSet::for_style()`) for the following draws.
+///
+/// # Safety
+/// `desc_set` must match set `first_set` of `pipeline_layout`, and must
+/// not be updated or dropped until the frame has finished.
+pub unsafe fn cmd_bind_descsets_at(connection: &Gpu,
+	pipeline_layout: VkPipelineLayout, first_set: u32,
+	desc_set: VkDescriptorSet)
+{
+	let connection = connection.get();
+
+	(connection.bind_descsets)(
+		connection.command_buffer,
+		VkPipelineBindPoint::Graphics,
+		pipeline_layout,
+		first_set,
+		1,
+		[desc_set].as_ptr(),
+		0,
+		null(),
+	);
+}
+
 pub unsafe fn cmd_bind_pipeline(connection: &Gpu, pipeline: VkPipeline) {
 	let connection = connection.get();
 
@@ -170,28 +271,54 @@ pub unsafe fn cmd_bind_pipeline(connection: &Gpu, pipeline: VkPipeline) {
 	);
 }
 
+/// Bind `vertex_buffers` to bindings 0 and up, from their starts.
+///
+/// # Safety
+/// The buffers must match the vertex bindings of the bound `Style`, and
+/// live until the frame has finished.
 #[inline(always)] pub unsafe fn cmd_bind_vb(connection: &Gpu,
 	vertex_buffers: &[VkBuffer])
 {
 	let connection = connection.get();
 
-	let offsets1 : [u64; 1] = [0];
-	let offsets2 : [u64; 2] = [0, 0];
-	let offsets3 : [u64; 3] = [0, 0, 0];
+	bind_vb(&connection, connection.command_buffer, 0, vertex_buffers,
+		&vec![0; vertex_buffers.len()]);
+}
 
-	let length = vertex_buffers.len();
+/// Bind `vertex_buffers` to bindings `first` and up, each starting at the
+/// byte offset in `offsets` (so sub-ranges of one `Buffer` can be bound).
+///
+/// # Safety
+/// The buffers must match the bound `Style`'s vertex bindings from `first`,
+/// each offset must be within its buffer, and the buffers must live until
+/// the frame has finished.
+pub unsafe fn cmd_bind_vb_offsets(connection: &Gpu, first: u32,
+	vertex_buffers: &[VkBuffer], offsets: &[u64])
+{
+	let connection = connection.get();
+
+	bind_vb(&connection, connection.command_buffer, first, vertex_buffers,
+		offsets);
+}
+
+unsafe fn bind_vb(connection: &gpu::GpuContext,
+	command_buffer: VkCommandBuffer, first: u32,
+	vertex_buffers: &[VkBuffer], offsets: &[u64])
+{
+	let max = connection.properties.limits.max_vertex_input_bindings;
+
+	assert_eq!(vertex_buffers.len(), offsets.len(),
+		"Need an offset for each vertex buffer");
+	assert!(!vertex_buffers.is_empty() && range_end(first,
+		vertex_buffers.len()).is_some_and(|end| end <= max),
+		"Wrong number of vertex buffers");
 
 	(connection.bind_vb)(
-		connection.command_buffer,
-		0,
-		length as u32,
+		command_buffer,
+		first,
+		vertex_buffers.len() as u32,
 		vertex_buffers.as_ptr(),
-		match length {
-			1 => offsets1.as_ptr(),
-			2 => offsets2.as_ptr(),
-			3 => offsets3.as_ptr(), 
-			_ => panic!("Wrong number of vertex buffers (Not 1-3)"),
-		},
+		offsets.as_ptr(),
 	);
 }
 
@@ -200,11 +327,411 @@ pub unsafe fn cmd_draw(connection: &Gpu, nvertices: u32, ninstances: u32,
 {
 	let connection = connection.get();
 
-	debug_assert!(nvertices > 2);
 	(connection.draw)(connection.command_buffer, nvertices, ninstances,
 		firstvertex, firstinstance);
 }
 
+/// Set the blend constants of the bound `Style`, which must have been built
+/// with `StyleBuilder::dynamic_blend_constants()`.
+///
+/// # Safety
+/// The `Gpu`'s command buffer must be recording.
+pub unsafe fn cmd_set_blend_constants(connection: &Gpu, rgba: [f32; 4]) {
+	let connection = connection.get();
+
+	(connection.set_blend_constants)(connection.command_buffer, &rgba);
+}
+
+/// Bind a compute pipeline (see `ComputeStyle`) for the following
+/// dispatches.
+///
+/// # Safety
+/// `pipeline` must be a compute pipeline that lives until the frame has
+/// finished.
+pub unsafe fn cmd_bind_compute(connection: &Gpu, pipeline: VkPipeline) {
+	let connection = connection.get();
+
+	(connection.bind_pipeline)(
+		connection.command_buffer,
+		VkPipelineBindPoint::Compute,
+		pipeline
+	);
+}
+
+/// Bind a descriptor set for the following dispatches.
+///
+/// # Safety
+/// `desc_set` must match set 0 of `pipeline_layout`, and must not be
+/// updated or dropped until the frame has finished.
+pub unsafe fn cmd_bind_compute_descsets(connection: &Gpu,
+	pipeline_layout: VkPipelineLayout, desc_set: VkDescriptorSet)
+{
+	let connection = connection.get();
+
+	(connection.bind_descsets)(
+		connection.command_buffer,
+		VkPipelineBindPoint::Compute,
+		pipeline_layout,
+		0,
+		1,
+		[desc_set].as_ptr(),
+		0,
+		null(),
+	);
+}
+
+/// Run the bound compute pipeline for `x` by `y` by `z` work groups.  Must
+/// be recorded outside of a render pass.
+///
+/// # Safety
+/// A compute pipeline and what it reads must be bound.
+pub unsafe fn cmd_dispatch(connection: &Gpu, x: u32, y: u32, z: u32) {
+	let connection = connection.get();
+
+	dispatch(&connection, connection.command_buffer, x, y, z);
+}
+
+unsafe fn dispatch(connection: &gpu::GpuContext,
+	command_buffer: VkCommandBuffer, x: u32, y: u32, z: u32)
+{
+	let max = connection.properties.limits.max_compute_work_group_count;
+
+	assert!(x <= max[0] && y <= max[1] && z <= max[2],
+		"Too many work groups, max is {:?}", max);
+
+	(connection.dispatch)(command_buffer, x, y, z);
+}
+
+/// Like `cmd_dispatch()`, but with the work group counts read from 3 `u32`s
+/// in `buffer` starting `offset` bytes in, so they can be written by the
+/// GPU.
+///
+/// # Safety
+/// A compute pipeline and what it reads must be bound, `offset` must be a
+/// multiple of 4 with the counts within `buffer`, and the counts must be
+/// within the GPU's limits (which can't be checked here).
+pub unsafe fn cmd_dispatch_indirect(connection: &Gpu, buffer: VkBuffer,
+	offset: u64)
+{
+	let connection = connection.get();
+
+	(connection.dispatch_indirect)(connection.command_buffer, buffer,
+		offset);
+}
+
+/// Make writes from earlier commands visible to later commands, as
+/// described by `barrier`.  Must be recorded outside of a render pass.
+///
+/// # Safety
+/// The `Gpu`'s command buffer must be recording.
+pub unsafe fn cmd_barrier(connection: &Gpu, barrier: Barrier) {
+	let connection = connection.get();
+
+	memory_barrier(&connection, connection.command_buffer, barrier);
+}
+
+unsafe fn memory_barrier(connection: &gpu::GpuContext,
+	command_buffer: VkCommandBuffer, barrier: Barrier)
+{
+	let (src_stage, dst_stage, src_access_mask, dst_access_mask)
+		= barrier.vk();
+
+	(connection.pipeline_barrier)(
+		command_buffer,
+		src_stage,
+		dst_stage,
+		0, 1, &VkMemoryBarrier {
+			s_type: VkStructureType::MemoryBarrier,
+			p_next: null(),
+			src_access_mask,
+			dst_access_mask,
+		}, 0, null(), 0, null());
+}
+
+/// Reset queries `first` to `first + count` of `pool`, so they can be written
+/// again.  Must be recorded outside of a render pass.
+///
+/// # Safety
+/// The queries must not be in use by a command buffer that hasn't finished.
+pub unsafe fn cmd_reset_queries(connection: &Gpu, pool: &QueryPool,
+	first: u32, count: u32)
+{
+	let connection = connection.get();
+
+	reset_queries(&connection, connection.command_buffer, pool, first,
+		count);
+}
+
+unsafe fn reset_queries(connection: &gpu::GpuContext,
+	command_buffer: VkCommandBuffer, pool: &QueryPool, first: u32,
+	count: u32)
+{
+	assert!(first.checked_add(count).is_some_and(|end| end <= pool.len()),
+		"Query out of range");
+
+	(connection.reset_queries)(command_buffer, pool.pool(), first, count);
+}
+
+/// Start counting for occlusion or pipeline statistics `query` of `pool`.
+/// With `precise`, occlusion queries count samples rather than only being
+/// zero or not zero (needs `Gpu::precise_occlusion()`).
+///
+/// # Safety
+/// `query` must have been reset since it was last used, and no other query
+/// of its kind may be active.
+pub unsafe fn cmd_begin_query(connection: &Gpu, pool: &QueryPool,
+	query: u32, precise: bool)
+{
+	let connection = connection.get();
+
+	begin_query(&connection, connection.command_buffer, pool, query,
+		precise);
+}
+
+unsafe fn begin_query(connection: &gpu::GpuContext,
+	command_buffer: VkCommandBuffer, pool: &QueryPool, query: u32,
+	precise: bool)
+{
+	assert!(pool.kind() != QueryKind::Timestamp,
+		"Timestamps are written, not begun");
+	assert!(query < pool.len(), "Query out of range");
+	assert!(!precise || (pool.kind() == QueryKind::Occlusion
+		&& connection.features.occlusion_query_precise != 0),
+		"Precise occlusion queries not supported");
+
+	(connection.begin_query)(command_buffer, pool.pool(), query,
+		precise as VkFlags);
+}
+
+/// Stop counting for `query` of `pool`, started with `cmd_begin_query()`.
+///
+/// # Safety
+/// `query` must have been begun in the same command buffer (and render pass,
+/// if begun inside of one).
+pub unsafe fn cmd_end_query(connection: &Gpu, pool: &QueryPool, query: u32) {
+	let connection = connection.get();
+
+	end_query(&connection, connection.command_buffer, pool, query);
+}
+
+unsafe fn end_query(connection: &gpu::GpuContext,
+	command_buffer: VkCommandBuffer, pool: &QueryPool, query: u32)
+{
+	assert!(pool.kind() != QueryKind::Timestamp,
+		"Timestamps are written, not ended");
+	assert!(query < pool.len(), "Query out of range");
+
+	(connection.end_query)(command_buffer, pool.pool(), query);
+}
+
+/// Write the GPU time to timestamp `query` of `pool` once all previous
+/// commands have reached `stage`.
+///
+/// # Safety
+/// `query` must have been reset since it was last written.
+pub unsafe fn cmd_write_timestamp(connection: &Gpu, pool: &QueryPool,
+	query: u32, stage: VkPipelineStage)
+{
+	let connection = connection.get();
+
+	write_timestamp(&connection, connection.command_buffer, pool, query,
+		stage);
+}
+
+unsafe fn write_timestamp(connection: &gpu::GpuContext,
+	command_buffer: VkCommandBuffer, pool: &QueryPool, query: u32,
+	stage: VkPipelineStage)
+{
+	assert!(pool.kind() == QueryKind::Timestamp,
+		"Not a timestamp query pool");
+	assert!(query < pool.len(), "Query out of range");
+
+	(connection.write_timestamp)(command_buffer, stage, pool.pool(),
+		query);
+}
+
+/// Set push constants of the pipeline layout for `stages`, starting `offset`
+/// bytes in, to `data`.  The range must be declared with
+/// `StyleBuilder::push_constants()`.
+///
+/// # Safety
+/// `T` must not have padding, and `offset` plus its size must be within a
+/// range for `stages` of `pipeline_layout`.
+pub unsafe fn cmd_push_constants<T: Copy>(connection: &Gpu,
+	pipeline_layout: VkPipelineLayout, stages: Stages, offset: u32,
+	data: &T)
+{
+	let connection = connection.get();
+
+	push_constants(&connection, connection.command_buffer,
+		pipeline_layout, stages, offset, data);
+}
+
+unsafe fn push_constants<T: Copy>(connection: &gpu::GpuContext,
+	command_buffer: VkCommandBuffer, pipeline_layout: VkPipelineLayout,
+	stages: Stages, offset: u32, data: &T)
+{
+	(connection.push_constants)(command_buffer, pipeline_layout, stages.0,
+		offset, mem::size_of::<T>() as u32,
+		data as *const T as *const c_void);
+}
+
+/// Bind the instance buffer for an instanced `style` (see
+/// `StyleBuilder::instances()`).
+///
+/// # Safety
+/// `instances` must hold an instance for each that is drawn, and live until
+/// the frame has finished.
+pub unsafe fn cmd_bind_instances(connection: &Gpu, style: &Style,
+	instances: VkBuffer)
+{
+	let connection = connection.get();
+
+	bind_instances(&connection, connection.command_buffer, style,
+		instances);
+}
+
+unsafe fn bind_instances(connection: &gpu::GpuContext,
+	command_buffer: VkCommandBuffer, style: &Style, instances: VkBuffer)
+{
+	let binding = style.instance_binding()
+		.expect("Style doesn't have per-instance attributes");
+
+	(connection.bind_vb)(command_buffer, binding, 1, &instances, &0);
+}
+
+/// Bind an index buffer, starting `offset` bytes in, for
+/// `cmd_draw_indexed()`.
+///
+/// # Safety
+/// `offset` must be a multiple of the index size, and `index_buffer` must
+/// live until the frame has finished.
+pub unsafe fn cmd_bind_ib(connection: &Gpu, index_buffer: VkBuffer,
+	offset: u64, index_type: IndexType)
+{
+	let connection = connection.get();
+
+	(connection.bind_ib)(connection.command_buffer, index_buffer, offset,
+		index_type.vk());
+}
+
+/// Draw `nindices` vertices from the bound index buffer, `ninstances` times.
+/// `vertexoffset` is added to each index.
+///
+/// # Safety
+/// The indices must be within the bound index buffer, and (once offset)
+/// refer to vertices within the bound vertex buffers.
+pub unsafe fn cmd_draw_indexed(connection: &Gpu, nindices: u32,
+	ninstances: u32, firstindex: u32, vertexoffset: i32, firstinstance: u32)
+{
+	let connection = connection.get();
+
+	(connection.draw_indexed)(connection.command_buffer, nindices,
+		ninstances, firstindex, vertexoffset, firstinstance);
+}
+
+/// Draw `ndraws` times, with the `DrawIndirectCommand`s in `buffer` starting
+/// `offset` bytes in.  More than 1 draw needs the GPU to support multi-draw
+/// indirect.
+///
+/// # Safety
+/// `buffer` must hold `ndraws` commands from `offset` (a multiple of 4),
+/// each within the bound vertex buffers.
+pub unsafe fn cmd_draw_indirect(connection: &Gpu, buffer: VkBuffer,
+	offset: u64, ndraws: u32)
+{
+	let connection = connection.get();
+
+	draw_indirect(&connection, connection.command_buffer, buffer, offset,
+		ndraws, false);
+}
+
+/// Like `cmd_draw_indirect()`, but with `DrawIndexedIndirectCommand`s.
+///
+/// # Safety
+/// `buffer` must hold `ndraws` commands from `offset` (a multiple of 4),
+/// each with indices within the bound index buffer that refer to vertices
+/// within the bound vertex buffers.
+pub unsafe fn cmd_draw_indexed_indirect(connection: &Gpu, buffer: VkBuffer,
+	offset: u64, ndraws: u32)
+{
+	let connection = connection.get();
+
+	draw_indirect(&connection, connection.command_buffer, buffer, offset,
+		ndraws, true);
+}
+
+unsafe fn draw_indirect(connection: &gpu::GpuContext,
+	command_buffer: VkCommandBuffer, buffer: VkBuffer, offset: u64,
+	ndraws: u32, indexed: bool)
+{
+	assert!(ndraws <= 1 || connection.features.multi_draw_indirect != 0,
+		"GPU doesn't support multi-draw indirect");
+	assert!(ndraws <= connection.properties.limits.max_draw_indirect_count,
+		"Too many indirect draws");
+
+	if indexed {
+		(connection.draw_indexed_indirect)(command_buffer, buffer,
+			offset, ndraws,
+			mem::size_of::<DrawIndexedIndirectCommand>() as u32);
+	} else {
+		(connection.draw_indirect)(command_buffer, buffer, offset,
+			ndraws, mem::size_of::<DrawIndirectCommand>() as u32);
+	}
+}
+
+/// Like `cmd_draw_indirect()`, but the number of draws is the `u32` in
+/// `count_buffer` at `count_offset` (at most `max_draws`), so it can be
+/// written by the GPU.  Panics unless `Gpu::draw_indirect_count()`.
+///
+/// # Safety
+/// `buffer` must hold `max_draws` commands from `offset` (a multiple of 4),
+/// each within the bound vertex buffers, and `count_offset` must be a
+/// multiple of 4 within `count_buffer`.
+pub unsafe fn cmd_draw_indirect_count(connection: &Gpu, buffer: VkBuffer,
+	offset: u64, count_buffer: VkBuffer, count_offset: u64, max_draws: u32)
+{
+	let connection = connection.get();
+
+	draw_indirect_count(&connection, connection.command_buffer, buffer,
+		offset, count_buffer, count_offset, max_draws, false);
+}
+
+/// Like `cmd_draw_indirect_count()`, but with `DrawIndexedIndirectCommand`s.
+///
+/// # Safety
+/// `buffer` must hold `max_draws` commands from `offset` (a multiple of 4),
+/// each with indices within the bound index buffer that refer to vertices
+/// within the bound vertex buffers, and `count_offset` must be a multiple
+/// of 4 within `count_buffer`.
+pub unsafe fn cmd_draw_indexed_indirect_count(connection: &Gpu,
+	buffer: VkBuffer, offset: u64, count_buffer: VkBuffer,
+	count_offset: u64, max_draws: u32)
+{
+	let connection = connection.get();
+
+	draw_indirect_count(&connection, connection.command_buffer, buffer,
+		offset, count_buffer, count_offset, max_draws, true);
+}
+
+unsafe fn draw_indirect_count(connection: &gpu::GpuContext,
+	command_buffer: VkCommandBuffer, buffer: VkBuffer, offset: u64,
+	count_buffer: VkBuffer, count_offset: u64, max_draws: u32,
+	indexed: bool)
+{
+	let (draw, stride) = if indexed {
+		(connection.draw_indexed_indirect_count,
+			mem::size_of::<DrawIndexedIndirectCommand>())
+	} else {
+		(connection.draw_indirect_count,
+			mem::size_of::<DrawIndirectCommand>())
+	};
+	let draw = draw.expect("GPU doesn't support indirect draw count");
+
+	draw(command_buffer, buffer, offset, count_buffer, count_offset,
+		max_draws, stride as u32);
+}
+
 pub unsafe fn new_semaphore(connection: &Gpu) -> VkSemaphore {
 	let connection = connection.get();
 
@@ -237,18 +764,38 @@ pub unsafe fn drop_semaphore(connection: &Gpu, semaphore: VkSemaphore) {
 pub unsafe fn draw_begin(connection: &Gpu, render_pass: VkRenderPass,
 	image: VkImage, frame_buffer: VkFramebuffer)
 {
-	let connection = connection.get();
+	let clear = default_clear(connection);
 
-	let begin_info = VkCommandBufferBeginInfo {
-		s_type: VkStructureType::CommandBufferBeginInfo,
-		p_next: null(),
-		flags: VkCommandBufferUsage::OneTimeSubmitBit,
-		p_inheritance_info: null(),
-	};
+	draw_begin_clear(connection, render_pass, image, frame_buffer,
+		&[clear.0, clear.1]);
+}
+
+/// Like `draw_begin()`, but with the color and depth attachments cleared to
+/// `clear[0]` and `clear[1]` instead of the clear color.  `Clear::Load`
+/// keeps what was drawn in the previous frame.  Panics unless `clear` has
+/// exactly those two values.
+///
+/// # Safety
+/// The `Gpu`'s command buffer must not still be executing, and `image` and
+/// `frame_buffer` must be from the swapchain that `render_pass` was created
+/// for.  An attachment can only be loaded once a previous frame has drawn
+/// to it, because it's in an undefined layout before then.
+pub unsafe fn draw_begin_clear(connection: &Gpu, render_pass: VkRenderPass,
+	image: VkImage, frame_buffer: VkFramebuffer, clear: &[Clear])
+{
+	let connection = connection.get();
 
-	(connection.begin_cmdbuff)(connection.command_buffer, &begin_info)
-		.unwrap();
+	begin_cmdbuff(&connection, connection.command_buffer);
+	begin_swapchain(&connection, connection.command_buffer, render_pass,
+		image, frame_buffer, clear, VkSubpassContents::Inline);
+}
 
+/// Transition the swapchain `image` for drawing, and begin the render pass.
+unsafe fn begin_swapchain(connection: &gpu::GpuContext,
+	command_buffer: VkCommandBuffer, render_pass: VkRenderPass,
+	image: VkImage, frame_buffer: VkFramebuffer, clear: &[Clear],
+	contents: VkSubpassContents)
+{
 	let layout_transition_barrier = VkImageMemoryBarrier {
 		s_type: VkStructureType::ImageMemoryBarrier,
 		p_next: null(),
@@ -269,19 +816,153 @@ pub unsafe fn draw_begin(connection: &Gpu, render_pass: VkRenderPass,
 	};
 
 	(connection.pipeline_barrier)(
-		connection.command_buffer,
+		command_buffer,
 		VkPipelineStage::TopOfPipe, 
 		VkPipelineStage::TopOfPipeAndColorAttachmentOutput,
 		0, 0, null(), 0, null(), 1, &layout_transition_barrier);
 
-	let rgb = connection.rgb;
-
 	// activate render pass:
-	let clear_value = [
-		VkClearValue { color: VkClearColorValue { float32: [rgb.x, rgb.y, rgb.z, 1.0] } },
-		VkClearValue { depth_stencil: VkClearDepthStencilValue { depth: 1.0, stencil: 0 } },
-	];
+	let clear_value: Vec<VkClearValue> = clear.iter()
+		.map(|c| c.value()).collect();
+	let render_pass = if clear.contains(&Clear::Load) {
+		swapchain_pass_for(connection, clear)
+	} else {
+		render_pass
+	};
+
+	begin_render_pass(connection, command_buffer, render_pass,
+		frame_buffer, connection.extent, &clear_value, contents);
+}
+
+/// Begin recording a frame that draws to an offscreen `RenderTarget`
+/// instead of the swapchain.
+///
+/// # Safety
+/// The `Gpu`'s command buffer must not still be executing, and `target`
+/// must not be dropped until the frame has finished.
+pub unsafe fn draw_begin_target(connection: &Gpu, target: &RenderTarget) {
+	let clear = default_clear(connection);
+
+	let mut clears = vec![clear.0; target.ncolors()];
+	if target.depth().is_some() {
+		clears.push(clear.1);
+	}
+
+	draw_begin_target_clear(connection, target, &clears);
+}
+
+/// Like `draw_begin_target()`, but with one `Clear` for each color image,
+/// followed by one for the depth image (if there is one).  `Clear::Load`
+/// keeps what was drawn to that image last time.  Panics if the number of
+/// `Clear`s doesn't match the number of images.
+///
+/// # Safety
+/// The `Gpu`'s command buffer must not still be executing, and `target`
+/// must not be dropped until the frame has finished.  An image can only be
+/// loaded once `target` has been drawn to, because it's in an undefined
+/// layout before then.
+pub unsafe fn draw_begin_target_clear(connection: &Gpu, target: &RenderTarget,
+	clear: &[Clear])
+{
+	let connection = connection.get();
+
+	begin_cmdbuff(&connection, connection.command_buffer);
+	begin_target(&connection, connection.command_buffer, target, clear,
+		VkSubpassContents::Inline);
+}
+
+/// Begin the render pass of `target` that loads or clears as in `clear`.
+unsafe fn begin_target(connection: &gpu::GpuContext,
+	command_buffer: VkCommandBuffer, target: &RenderTarget,
+	clear: &[Clear], contents: VkSubpassContents)
+{
+	let load: Vec<bool> = clear.iter().map(|c| *c == Clear::Load)
+		.collect();
+	let render_pass = target.render_pass_for(&load);
+
+	// Multisampled color attachments, then depth (resolved attachments
+	// aren't cleared).
+	let clear_value: Vec<VkClearValue> = clear.iter()
+		.map(|c| c.value()).collect();
+
+	begin_render_pass(connection, command_buffer, render_pass,
+		target.framebuffer(), target.extent(), &clear_value, contents);
+}
+
+/// Begin recording `command_buffer`, to be submitted once.
+unsafe fn begin_cmdbuff(connection: &gpu::GpuContext,
+	command_buffer: VkCommandBuffer)
+{
+	let begin_info = VkCommandBufferBeginInfo {
+		s_type: VkStructureType::CommandBufferBeginInfo,
+		p_next: null(),
+		flags: VkCommandBufferUsage::OneTimeSubmitBit,
+		p_inheritance_info: null(),
+	};
 
+	(connection.begin_cmdbuff)(command_buffer, &begin_info).unwrap();
+}
+
+/// The clear color, and a depth of 1.0.
+fn default_clear(connection: &Gpu) -> (Clear, Clear) {
+	let rgb = connection.get().rgb;
+
+	(Clear::Color([rgb.x, rgb.y, rgb.z, 1.0]), Clear::DepthStencil(1.0, 0))
+}
+
+/// Clear a rectangle of the current render pass's attachments: each
+/// `Clear::Color` is applied to the color attachment at the paired index,
+/// and a `Clear::DepthStencil` to the depth attachment.
+///
+/// # Safety
+/// Must be recorded inside of a render pass, and the rectangle must be
+/// inside of its render area.
+pub unsafe fn cmd_clear_attachments(connection: &Gpu, clear: &[(u32, Clear)],
+	x: i32, y: i32, width: u32, height: u32)
+{
+	let connection = connection.get();
+
+	clear_attachments(&connection, connection.command_buffer, clear,
+		Scissor::new(x, y, width, height));
+}
+
+unsafe fn clear_attachments(connection: &gpu::GpuContext,
+	command_buffer: VkCommandBuffer, clear: &[(u32, Clear)], rect: Scissor)
+{
+	let attachments: Vec<VkClearAttachment> = clear.iter()
+		.filter(|c| c.1 != Clear::Load)
+		.map(|&(index, c)| VkClearAttachment {
+			aspect_mask: match c {
+				Clear::DepthStencil(_, _) =>
+					VkImageAspectFlags::Depth,
+				_ => VkImageAspectFlags::Color,
+			},
+			color_attachment: index,
+			clear_value: c.value(),
+		}).collect();
+
+	if attachments.is_empty() {
+		return;
+	}
+
+	(connection.clear_attachments)(
+		command_buffer,
+		attachments.len() as u32,
+		attachments.as_ptr(),
+		1,
+		&VkClearRect {
+			rect: rect.vk(),
+			base_array_layer: 0,
+			layer_count: 1,
+		},
+	);
+}
+
+unsafe fn begin_render_pass(connection: &gpu::GpuContext,
+	command_buffer: VkCommandBuffer, render_pass: VkRenderPass,
+	frame_buffer: VkFramebuffer, extent: VkExtent2D,
+	clear_value: &[VkClearValue], contents: VkSubpassContents)
+{
 	let render_pass_begin_info = VkRenderPassBeginInfo {
 		s_type: VkStructureType::RenderPassBeginInfo,
 		p_next: null(),
@@ -289,17 +970,25 @@ pub unsafe fn draw_begin(connection: &Gpu, render_pass: VkRenderPass,
 		framebuffer: frame_buffer,
 		render_area: VkRect2D {
 			offset: VkOffset2D { x: 0, y: 0 },
-			extent: connection.extent,
+			extent,
 		},
 		clear_value_count: clear_value.len() as u32,
 		p_clear_values: clear_value.as_ptr(),
 	};
+	// Secondary command buffers set their own dynamic state.
+	let inline = match contents {
+		VkSubpassContents::Inline => true,
+		VkSubpassContents::SecondaryCommandBuffers => false,
+	};
+
 	(connection.begin_render)(
-		connection.command_buffer,
+		command_buffer,
 		&render_pass_begin_info,
-		VkSubpassContents::Inline
+		contents
 	);
-	dynamic_state(&connection, connection.command_buffer);
+	if inline {
+		dynamic_state(connection, command_buffer, extent);
+	}
 }
 
 pub unsafe fn end_render_pass(connection: &Gpu) {
@@ -308,26 +997,114 @@ pub unsafe fn end_render_pass(connection: &Gpu) {
 	(connection.end_render_pass)(connection.command_buffer);
 }
 
-/// Update the dynamic state (resize viewport).
+/// Update the dynamic state (resize viewport), covering the whole framebuffer
+/// with every viewport a `Style` may use.
 unsafe fn dynamic_state(connection: &gpu::GpuContext,
-	command_buffer: VkCommandBuffer)
+	command_buffer: VkCommandBuffer, extent: VkExtent2D)
+{
+	let count = connection.max_viewports();
+	let viewport = Viewport::new(0.0, 0.0, extent.width as f32,
+		extent.height as f32).vk();
+	let scissor = Scissor::new(0, 0, extent.width, extent.height).vk();
+
+	(connection.set_viewport)(command_buffer, 0, count,
+		vec![viewport; count as usize].as_ptr());
+	(connection.set_scissor)(command_buffer, 0, count,
+		vec![scissor; count as usize].as_ptr());
+}
+
+/// Set the viewport that is drawn to, until the next render pass begins.
+///
+/// # Safety
+/// Must be recorded while the `Gpu`'s command buffer is recording.
+pub unsafe fn cmd_set_viewport(connection: &Gpu, viewport: Viewport) {
+	cmd_set_viewports(connection, 0, &[viewport]);
+}
+
+/// Set `viewports`, starting at index `first`.  Only a `Style` built with
+/// `StyleBuilder::viewports()` uses more than the first one.
+///
+/// # Safety
+/// The `Gpu`'s command buffer must be recording, and the viewports must have
+/// a positive size.
+pub unsafe fn cmd_set_viewports(connection: &Gpu, first: u32,
+	viewports: &[Viewport])
+{
+	let connection = connection.get();
+
+	set_viewports(&connection, connection.command_buffer, first,
+		viewports);
+}
+
+unsafe fn set_viewports(connection: &gpu::GpuContext,
+	command_buffer: VkCommandBuffer, first: u32, viewports: &[Viewport])
+{
+	let max = connection.max_viewports();
+
+	assert!(range_end(first, viewports.len()).is_some_and(|end| end <= max),
+		"GPU supports only {} viewport(s)", max);
+
+	let viewports: Vec<VkViewport> = viewports.iter()
+		.map(|viewport| viewport.vk())
+		.collect();
+
+	(connection.set_viewport)(command_buffer, first,
+		viewports.len() as u32, viewports.as_ptr());
+}
+
+/// Set the scissor rectangle, until the next render pass begins.
+///
+/// # Safety
+/// Must be recorded while the `Gpu`'s command buffer is recording.
+pub unsafe fn cmd_set_scissor(connection: &Gpu, scissor: Scissor) {
+	cmd_set_scissors(connection, 0, &[scissor]);
+}
+
+/// Set the scissor rectangles for `viewports` starting at index `first`.
+///
+/// # Safety
+/// The `Gpu`'s command buffer must be recording, and the rectangles must not
+/// overflow.
+pub unsafe fn cmd_set_scissors(connection: &Gpu, first: u32,
+	scissors: &[Scissor])
 {
-	(connection.set_viewport)(command_buffer, 0, 1, &VkViewport {
-		x: 0.0, y: 0.0,
-		width: connection.extent.width as f32,
-		height: connection.extent.height as f32,
-		min_depth: 0.0,
-		max_depth: 1.0,
-	});
-	(connection.set_scissor)(command_buffer, 0, 1, &VkRect2D {
-		offset: VkOffset2D { x: 0, y: 0 },
-		extent: connection.extent,
-	});
+	let connection = connection.get();
+
+	set_scissors(&connection, connection.command_buffer, first, scissors);
+}
+
+unsafe fn set_scissors(connection: &gpu::GpuContext,
+	command_buffer: VkCommandBuffer, first: u32, scissors: &[Scissor])
+{
+	let max = connection.max_viewports();
+
+	assert!(range_end(first, scissors.len()).is_some_and(|end| end <= max),
+		"GPU supports only {} viewport(s)", max);
+
+	let scissors: Vec<VkRect2D> = scissors.iter()
+		.map(|scissor| scissor.vk())
+		.collect();
+
+	(connection.set_scissor)(command_buffer, first,
+		scissors.len() as u32, scissors.as_ptr());
+}
+
+/// Get the index after `len` items starting at `first`, if it fits in a
+/// `u32`.
+pub(crate) fn range_end(first: u32, len: usize) -> Option<u32> {
+	u32::try_from(len).ok().and_then(|len| first.checked_add(len))
 }
 
 pub unsafe fn pipeline_barrier(connection: &Gpu, image: VkImage) {
 	let connection = connection.get();
 
+	present_barrier(&connection, connection.command_buffer, image);
+}
+
+/// Transition the swapchain `image` to be presented.
+unsafe fn present_barrier(connection: &gpu::GpuContext,
+	command_buffer: VkCommandBuffer, image: VkImage)
+{
 	let barrier = VkImageMemoryBarrier {
 		s_type: VkStructureType::ImageMemoryBarrier,
 		p_next: null(),
@@ -348,7 +1125,7 @@ pub unsafe fn pipeline_barrier(connection: &Gpu, image: VkImage) {
 	};
 
 	(connection.pipeline_barrier)(
-		connection.command_buffer,
+		command_buffer,
 		VkPipelineStage::AllCommands, 
 		VkPipelineStage::BottomOfPipe, 
 		0, 0, null(), 0, null(), 1, &barrier);
@@ -388,8 +1165,16 @@ pub unsafe fn get_buffering(connection: &Gpu) -> u32 {
 {
 	let connection = connection.get();
 
+	copy(&connection, connection.command_buffer, src_image, dst_image,
+		width, height);
+}
+
+unsafe fn copy(connection: &gpu::GpuContext,
+	command_buffer: VkCommandBuffer, src_image: &Image, dst_image: &Image,
+	width: u16, height: u16)
+{
 	(connection.copy_image)(
-		connection.command_buffer,
+		command_buffer,
 		src_image.image().0, VkImageLayout::TransferSrcOptimal,
 		dst_image.image().0, VkImageLayout::TransferDstOptimal, 1,
 		&VkImageCopy {
@@ -430,6 +1215,15 @@ pub unsafe fn get_buffering(connection: &Gpu) -> u32 {
 	// Update extent.
 	connection.extent = surface_info.max_image_extent;
 
+	// Allow reading back the swapchain images, if supported.
+	connection.readable = surface_info.supported_usage_flags
+		& (VkImageUsage::TransferSrcBit as VkFlags) != 0;
+	let image_usage = if connection.readable {
+		VkImageUsage::ColorAttachmentTransferSrc
+	} else {
+		VkImageUsage::ColorAttachmentBit
+	};
+
 	(connection.new_swapchain)(
 		connection.device,
 		&VkSwapchainCreateInfoKHR {
@@ -442,7 +1236,7 @@ pub unsafe fn get_buffering(connection: &Gpu) -> u32 {
 			image_color_space: VkColorSpaceKHR::SrgbNonlinearKhr,
 			image_extent: connection.extent,
 			image_array_layers: 1,
-			image_usage: VkImageUsage::ColorAttachmentBit,
+			image_usage,
 			image_sharing_mode: VkSharingMode::Exclusive,
 			pre_transform: VkSurfaceTransformFlagBitsKHR::Identity,
 			composite_alpha: VkCompositeAlphaFlagBitsKHR::Opaque,
@@ -527,6 +1321,14 @@ pub unsafe fn queue_submit(connection: &Gpu, submit_fence: &Fence,
 {
 	let connection = connection.get();
 
+	submit(&connection, connection.command_buffer, submit_fence,
+		pipelane_stage, semaphore);
+}
+
+unsafe fn submit(connection: &gpu::GpuContext,
+	command_buffer: VkCommandBuffer, submit_fence: &Fence,
+	pipelane_stage: VkPipelineStage, semaphore: Option<VkSemaphore>)
+{
 	(connection.queue_submit)(
 		connection.present_queue,
 		1,
@@ -537,7 +1339,7 @@ pub unsafe fn queue_submit(connection: &Gpu, submit_fence: &Fence,
 			wait_semaphores: null(),
 			wait_dst_stage_mask: &pipelane_stage,
 			command_buffer_count: 1,
-			p_command_buffers: &connection.command_buffer,
+			p_command_buffers: &command_buffer,
 			signal_semaphore_count: if semaphore.is_none() { 0 }
 				else { 1 },
 			p_signal_semaphores: if let Some(ref sem) = semaphore {
@@ -619,8 +1421,9 @@ pub unsafe fn create_ms_buffer(vulkan: &Gpu) -> Image {
 
 	Image::new(vulkan, extent.width, extent.height,
 		vulkan.get().format.clone(), VkImageTiling::Optimal,
-		VkImageUsage::TransientColorAttachment,
-		VkImageLayout::Undefined, 0, VK_SAMPLE_COUNT)
+		// Not transient, so it can be loaded in the next frame.
+		VkImageUsage::ColorAttachmentBit,
+		VkImageLayout::Undefined, 0, vulkan.get().sample_count)
 }
 
 #[inline(always)] pub unsafe fn create_depth_buffer(
@@ -631,7 +1434,7 @@ pub unsafe fn create_ms_buffer(vulkan: &Gpu) -> Image {
 	let image = Image::new(vulkan, extent.width, extent.height,
 		VkFormat::D16Unorm, VkImageTiling::Optimal,
 		VkImageUsage::DepthStencilAttachmentBit,
-		VkImageLayout::Undefined, 0, VK_SAMPLE_COUNT);
+		VkImageLayout::Undefined, 0, vulkan.get().sample_count);
 
 	// before using this depth buffer we must change it's layout:
 	(vulkan.get().begin_cmdbuff)(
@@ -690,8 +1493,56 @@ pub unsafe fn create_ms_buffer(vulkan: &Gpu) -> Image {
 
 #[inline(always)]
 pub unsafe fn create_render_pass(connection: &Gpu) -> VkRenderPass {
-	let connection = connection.get();
+	swapchain_render_pass(&connection.get(), &[false, false])
+}
+
+/// Get a render pass compatible with `create_render_pass()` that loads the
+/// attachments that are `Clear::Load` in `clear` (color, then depth).
+unsafe fn swapchain_pass_for(connection: &gpu::GpuContext, clear: &[Clear])
+	-> VkRenderPass
+{
+	assert_eq!(clear.len(), 2, "Need a Clear for color and depth");
+
+	let load: Vec<bool> = clear.iter().map(|c| *c == Clear::Load)
+		.collect();
+	let mut load_passes = connection.swapchain_load_passes.borrow_mut();
+
+	for &(ref which, render_pass) in load_passes.iter() {
+		if *which == load {
+			return render_pass;
+		}
+	}
+
+	let render_pass = swapchain_render_pass(connection, &load);
+
+	load_passes.push((load, render_pass));
+
+	render_pass
+}
+
+/// `load` has whether or not to load the color, then depth attachment.  Both
+/// are stored, so that they can be loaded in the next frame.
+unsafe fn swapchain_render_pass(connection: &gpu::GpuContext, load: &[bool])
+	-> VkRenderPass
+{
 	let mut render_pass = mem::uninitialized();
+	let (color_load_op, color_layout) = render_target::load_or_clear(
+		load[0], VkImageLayout::ColorAttachmentOptimal);
+	let (depth_load_op, depth_layout) = render_target::load_or_clear(
+		load[1], VkImageLayout::DepthStencilAttachmentOptimal);
+	let mut dependencies = vec![VkSubpassDependency {
+		src_subpass: !0,
+		dst_subpass: 0,
+		src_stage_mask: VkPipelineStage::ColorAttachmentOutput,
+		dst_stage_mask: VkPipelineStage::ColorAttachmentOutput,
+		src_access_mask: VkAccess::ColorAttachmentWriteBit,
+		dst_access_mask: VkAccess::ColorAttachmentReadWrite,
+		dependency_flags: 0,
+	}];
+
+	if load[1] {
+		dependencies.push(render_target::depth_load_dependency());
+	}
 
 	(connection.new_renderpass)(
 		connection.device,
@@ -705,15 +1556,14 @@ pub unsafe fn create_render_pass(connection: &Gpu) -> VkRenderPass {
 				VkAttachmentDescription {
 					flags: 0,
 					format: connection.format.clone(),
-					samples: VK_SAMPLE_COUNT,
-					load_op: VkAttachmentLoadOp::Clear,
-					store_op: VkAttachmentStoreOp::DontCare,
+					samples: connection.sample_count,
+					load_op: color_load_op,
+					store_op: VkAttachmentStoreOp::Store,
 					stencil_load_op:
 						VkAttachmentLoadOp::DontCare,
 					stencil_store_op:
 						VkAttachmentStoreOp::DontCare,
-					initial_layout:
-					  VkImageLayout::Undefined,
+					initial_layout: color_layout,
 					final_layout:
 					  VkImageLayout::ColorAttachmentOptimal,
 				},
@@ -721,15 +1571,14 @@ pub unsafe fn create_render_pass(connection: &Gpu) -> VkRenderPass {
 				VkAttachmentDescription {
 					flags: 0,
 					format: VkFormat::D16Unorm,
-					samples: VK_SAMPLE_COUNT,
-					load_op: VkAttachmentLoadOp::Clear,
-					store_op: VkAttachmentStoreOp::DontCare,
+					samples: connection.sample_count,
+					load_op: depth_load_op,
+					store_op: VkAttachmentStoreOp::Store,
 					stencil_load_op:
 						VkAttachmentLoadOp::DontCare,
 					stencil_store_op:
 						VkAttachmentStoreOp::DontCare,
-					initial_layout:
-					 VkImageLayout::DepthStencilAttachmentOptimal,
+					initial_layout: depth_layout,
 					final_layout:
 					 VkImageLayout::DepthStencilAttachmentOptimal,
 				},
@@ -777,16 +1626,8 @@ pub unsafe fn create_render_pass(connection: &Gpu) -> VkRenderPass {
 					 VkImageLayout::PresentSrc,
 				},
 			},
-			dependency_count: 1,
-			dependencies: &VkSubpassDependency {
-				src_subpass: !0,
-				dst_subpass: 0,
-				src_stage_mask: VkPipelineStage::ColorAttachmentOutput,
-				dst_stage_mask: VkPipelineStage::ColorAttachmentOutput,
-				src_access_mask: VkAccess::ColorAttachmentWriteBit,
-				dst_access_mask: VkAccess::ColorAttachmentReadWrite,
-				dependency_flags: 0,
-			},
+			dependency_count: dependencies.len() as u32,
+			dependencies: dependencies.as_ptr(),
 		},
 		null(),
 		&mut render_pass
@@ -840,8 +1681,13 @@ pub unsafe fn create_render_pass(connection: &Gpu) -> VkRenderPass {
 		(connection.drop_framebuffer)(device, frame_buffers[i], null());
 		(connection.drop_imgview)(device, present_imgviews[i], null());
 	}
-	// Free render pass
+	// Free render passes
 	(connection.drop_renderpass)(device, render_pass, null());
+	for (_, render_pass) in connection.swapchain_load_passes.borrow_mut()
+		.drain(..)
+	{
+		(connection.drop_renderpass)(device, render_pass, null());
+	}
 	// Free swapchain
 	(connection.drop_swapchain)(device, connection.swapchain, null());
 }
@@ -875,16 +1721,76 @@ pub unsafe fn new_buffer(vulkan: &Gpu, vertices: &[f32]) -> Buffer {
 	Buffer::new(vulkan, vertices, BufferBuilderType::Vertex)
 }
 
-pub struct ShaderModule(
-	VkShaderModule,
-	// TODO: Don't
-	VkDevice,
-	unsafe extern "system" fn(VkDevice, VkShaderModule, *const c_void) -> (),
-);
+/// A SPIR-V shader module, and which of its entry points (and stage)
+/// pipelines made from it use.
+#[derive(Clone)] pub struct ShaderModule {
+	module: Rc<ShaderModuleContext>,
+	stage: Stages,
+	name: CString,
+}
+
+struct ShaderModuleContext {
+	shader: VkShaderModule,
+	entry_points: Vec<(Stages, String)>,
+	reflection: Result<Reflection, String>,
+	vulkan: Gpu,
+}
 
 impl ShaderModule {
-	/// Load a new shader module into memory.
+	/// Load a new shader module into memory, using its first entry point.
+	/// `spirv_shader` may be in either byte order, and doesn't need to be
+	/// aligned.  Panics if it isn't SPIR-V.
 	pub fn new(connection: &Gpu, spirv_shader: &[u8]) -> ShaderModule {
+		Self::try_new(connection, spirv_shader)
+			.unwrap_or_else(|e| panic!("Bad SPIR-V: {}", e))
+	}
+
+	/// Like `new()`, but returns why `spirv_shader` isn't SPIR-V instead
+	/// of panicking.
+	pub fn try_new(connection: &Gpu, spirv_shader: &[u8])
+		-> Result<ShaderModule, String>
+	{
+		Self::try_from_words(connection, &reflect::words(spirv_shader)?)
+	}
+
+	/// Compile GLSL or WGSL `source` into a new shader module, using its
+	/// first entry point.  The source may use what the GPU's enabled
+	/// features allow.  Needs the `compiler` feature.
+	#[cfg(feature = "compiler")]
+	pub fn compile(connection: &Gpu, source: &str, language: Language)
+		-> Result<ShaderModule, Vec<CompileError>>
+	{
+		let capabilities = compiler::capabilities(
+			&connection.get().features);
+		let words = compiler::compile_with(source, language,
+			capabilities)?;
+
+		Self::try_from_words(connection, &words)
+			.map_err(|e| vec![CompileError::new(None, e)])
+	}
+
+	/// Like `new()`, but from SPIR-V words in the machine's byte order.
+	pub fn from_words(connection: &Gpu, spirv_shader: &[u32])
+		-> ShaderModule
+	{
+		Self::try_from_words(connection, spirv_shader)
+			.unwrap_or_else(|e| panic!("Bad SPIR-V: {}", e))
+	}
+
+	/// Like `from_words()`, but returns why `spirv_shader` isn't SPIR-V
+	/// instead of panicking.
+	pub fn try_from_words(connection: &Gpu, spirv_shader: &[u32])
+		-> Result<ShaderModule, String>
+	{
+		if spirv_shader.len() < 5 || spirv_shader[0] != reflect::MAGIC {
+			return Err("Not SPIR-V".to_string());
+		}
+
+		let entry_points = reflect::entry_points(spirv_shader);
+		let (stage, name) = entry_points.first().cloned()
+			.ok_or("No entry points")?;
+		let reflection = reflect::reflect(spirv_shader);
+		let vulkan = connection;
 		let connection = connection.get();
 
 		let mut shader = unsafe { mem::uninitialized() };
@@ -896,7 +1802,7 @@ impl ShaderModule {
 					s_type: VkStructureType::ShaderModuleCreateInfo,
 					next: null(),
 					flags: 0,
-					code_size: spirv_shader.len(),
+					code_size: mem::size_of_val(spirv_shader),
 					code: spirv_shader.as_ptr(),
 				},
 				null(),
@@ -904,14 +1810,77 @@ impl ShaderModule {
 			).unwrap();
 		}
 
-		ShaderModule(shader, connection.device, connection.drop_shademod)
+		Ok(ShaderModule {
+			module: Rc::new(ShaderModuleContext {
+				shader, entry_points, reflection,
+				vulkan: vulkan.clone(),
+			}),
+			stage,
+			name: CString::new(name).unwrap(),
+		})
+	}
+
+	/// Use the entry point `name` for `stage` of the same module instead.
+	/// Panics if the module doesn't have it.
+	pub fn entry_point(&self, stage: Stages, name: &str) -> ShaderModule {
+		assert!(self.module.entry_points.iter()
+			.any(|&(s, ref n)| s == stage && n == name),
+			"Shader module has no {:?} entry point `{}`", stage, name);
+
+		ShaderModule {
+			module: self.module.clone(),
+			stage,
+			name: CString::new(name).unwrap(),
+		}
+	}
+
+	/// Get the stage of the entry point used.
+	pub fn stage(&self) -> Stages {
+		self.stage
+	}
+
+	/// Get the name of the entry point used.
+	pub fn name(&self) -> &str {
+		self.name.to_str().unwrap()
+	}
+
+	/// Get the entry points, descriptors, push constants and interface
+	/// variables declared by the shader, or why they couldn't be found.
+	pub fn reflection(&self) -> Result<&Reflection, &str> {
+		match self.module.reflection {
+			Ok(ref reflection) => Ok(reflection),
+			Err(ref error) => Err(error),
+		}
+	}
+
+	/// Get the pipeline stage for the entry point used, which must be
+	/// for `stage`, with its specialization constants (if any).
+	pub(crate) fn stage_info(&self, stage: Stages,
+		specialization: Option<&VkSpecializationInfo>)
+		-> VkPipelineShaderStageCreateInfo
+	{
+		assert!(self.stage == stage, "Expected a {:?} shader, not {:?}",
+			stage, self.stage);
+
+		VkPipelineShaderStageCreateInfo {
+			s_type: VkStructureType::PipelineShaderStageCreateInfo,
+			next: null(),
+			flags: 0,
+			stage: stage.vk(),
+			module: self.module.shader,
+			name: self.name.as_ptr() as *const u8,
+			specialization_info: specialization
+				.map(|s| s as *const _).unwrap_or(null()),
+		}
 	}
 }
 
-impl Drop for ShaderModule {
+impl Drop for ShaderModuleContext {
 	fn drop(&mut self) -> () {
+		let vk = self.vulkan.get();
+
 		unsafe {
-			(self.2)(self.1, self.0, null());
+			(vk.drop_shademod)(vk.device, self.shader, null());
 		}
 	}
 }
@@ -18,6 +18,40 @@ use std::{ rc::Rc };
 pub enum BufferBuilderType {
 	Uniform,
 	Vertex,
+	/// A buffer that images are copied into, to be read by the CPU.
+	Readback,
+	/// A buffer of vertex indices, for `cmd_draw_indexed()`.
+	Index,
+	/// A buffer of `DrawIndirectCommand`s or `DrawIndexedIndirectCommand`s
+	/// (and draw counts), for `cmd_draw_indirect()` and friends.
+	Indirect,
+	/// A storage buffer (SSBO), which shaders can read and write.
+	Storage,
+	/// A storage buffer that's also a vertex buffer, for example, vertices
+	/// written by a compute shader.
+	StorageVertex,
+	/// A buffer read as formatted texels through a `BufferView`.
+	UniformTexel,
+	/// A buffer read and written as formatted texels through a
+	/// `BufferView`.
+	StorageTexel,
+}
+
+/// The integer type of the indices in an index buffer.
+#[derive(Copy, Clone, Debug, PartialEq)] pub enum IndexType {
+	/// `u16` indices, restarting primitives at 0xFFFF.
+	U16,
+	/// `u32` indices, restarting primitives at 0xFFFFFFFF.
+	U32,
+}
+
+impl IndexType {
+	pub(crate) fn vk(&self) -> VkIndexType {
+		match *self {
+			IndexType::U16 => VkIndexType::Uint16,
+			IndexType::U32 => VkIndexType::Uint32,
+		}
+	}
 }
 
 /// A buffer in GPU memory.
@@ -52,6 +86,20 @@ impl Buffer {
 					    VkBufferUsage::UniformBufferBit,
 					  BufferBuilderType::Vertex =>
 					    VkBufferUsage::VertexIndexBufferBit,
+					  BufferBuilderType::Readback =>
+					    VkBufferUsage::TransferDstBit,
+					  BufferBuilderType::Index =>
+					    VkBufferUsage::IndexBufferBit,
+					  BufferBuilderType::Indirect =>
+					    VkBufferUsage::IndirectBufferBit,
+					  BufferBuilderType::Storage =>
+					    VkBufferUsage::StorageBufferBit,
+					  BufferBuilderType::StorageVertex =>
+					    VkBufferUsage::StorageVertexBufferBit,
+					  BufferBuilderType::UniformTexel =>
+					    VkBufferUsage::UniformTexelBufferBit,
+					  BufferBuilderType::StorageTexel =>
+					    VkBufferUsage::StorageTexelBufferBit,
 					},
 					sharing_mode: VkSharingMode::Exclusive,
 					queue_family_index_count: 0,
@@ -151,3 +199,58 @@ impl Drop for BufferContext {
 		}
 	}
 }
+
+/// A view of a `UniformTexel` or `StorageTexel` buffer as an array of texels
+/// of one format.
+#[derive(Clone)] pub struct BufferView(Rc<BufferViewContext>);
+
+struct BufferViewContext {
+	view: VkBufferView,
+	#[allow(unused)] // To keep in scope, preventing segfault.
+	buffer: Buffer,
+	vulkan: Gpu,
+}
+
+impl BufferView {
+	/// Create a view of all of `buffer`, as texels of `format`.
+	pub fn new(vulkan: &Gpu, buffer: &Buffer, format: VkFormat) -> Self {
+		let connection = vulkan.get();
+		let mut view = 0;
+
+		unsafe {
+			(connection.new_bufview)(
+				connection.device,
+				&VkBufferViewCreateInfo {
+					s_type: VkStructureType::BufferViewCreateInfo,
+					next: ptr::null(),
+					flags: 0,
+					buffer: buffer.buffer(),
+					format,
+					offset: 0,
+					range: !0,
+				},
+				ptr::null(),
+				&mut view
+			).unwrap();
+		}
+
+		BufferView(Rc::new(BufferViewContext {
+			view, buffer: buffer.clone(), vulkan: vulkan.clone(),
+		}))
+	}
+
+	/// Get the `VkBufferView`, for descriptor writes.
+	pub fn view(&self) -> VkBufferView {
+		self.0.view
+	}
+}
+
+impl Drop for BufferViewContext {
+	fn drop(&mut self) {
+		let vulkan = self.vulkan.get();
+
+		unsafe {
+			(vulkan.drop_bufview)(vulkan.device, self.view, null());
+		}
+	}
+}
@@ -9,7 +9,9 @@ use Gpu;
 
 mod buffer;
 
-pub use self::buffer::{ Buffer, BufferBuilderType };
+pub use self::buffer::{
+	Buffer, BufferBuilderType, BufferView, IndexType
+};
 
 // TODO: is needed?  Probably just use buffer instead.
 pub struct Memory<T> where T: Clone {
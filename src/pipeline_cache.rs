@@ -0,0 +1,129 @@
+// "asi_vulkan" - Aldaron's System Interface - Vulkan
+//
+// Copyright Jeron A. Lau 2018.
+// Distributed under the Boost Software License, Version 1.0.
+// (See accompanying file LICENSE_1_0.txt or copy at
+// https://www.boost.org/LICENSE_1_0.txt)
+
+use null;
+use null_mut;
+use c_void;
+
+use Gpu;
+use types::*;
+use std::{ fs, io, path::Path, rc::Rc };
+
+/// Length of a version one pipeline cache header.
+const HEADER_SIZE: usize = 32;
+
+/// Compiled pipelines, shared between `Style`s and `ComputeStyle`s, that can
+/// be saved to a file so they don't need to be compiled again on the next run.
+#[derive(Clone)] pub struct PipelineCache(Rc<PipelineCacheContext>);
+
+struct PipelineCacheContext {
+	cache: VkPipelineCache,
+	vulkan: Gpu,
+}
+
+impl PipelineCache {
+	/// Create an empty pipeline cache.
+	pub fn new(vulkan: &Gpu) -> Self {
+		Self::with_data(vulkan, &[])
+	}
+
+	/// Load a pipeline cache saved with `save()`.  If the file is missing,
+	/// or was saved by a different GPU or driver, start with an empty
+	/// cache instead.
+	pub fn load<P: AsRef<Path>>(vulkan: &Gpu, path: P) -> Self {
+		match fs::read(path) {
+			Ok(ref data) if valid(vulkan, data) => {
+				Self::with_data(vulkan, data)
+			}
+			_ => Self::new(vulkan),
+		}
+	}
+
+	fn with_data(vulkan: &Gpu, data: &[u8]) -> Self {
+		let connection = vulkan.get();
+		let mut cache = VkPipelineCache(0);
+
+		unsafe {
+			(connection.new_pipeline_cache)(
+				connection.device,
+				&VkPipelineCacheCreateInfo {
+					s_type: VkStructureType::PipelineCacheCreateInfo,
+					next: null(),
+					flags: 0,
+					initial_data_size: data.len(),
+					initial_data: data.as_ptr() as *const c_void,
+				},
+				null(),
+				&mut cache
+			).unwrap();
+		}
+
+		PipelineCache(Rc::new(PipelineCacheContext {
+			cache, vulkan: vulkan.clone(),
+		}))
+	}
+
+	/// Get the contents of the cache, header included.
+	pub fn data(&self) -> Vec<u8> {
+		let connection = self.0.vulkan.get();
+		let mut size = 0;
+
+		unsafe {
+			(connection.pipeline_cache_data)(connection.device,
+				self.0.cache, &mut size, null_mut()).unwrap();
+		}
+
+		let mut data = vec![0u8; size];
+
+		unsafe {
+			(connection.pipeline_cache_data)(connection.device,
+				self.0.cache, &mut size,
+				data.as_mut_ptr() as *mut c_void).unwrap();
+		}
+
+		data.truncate(size);
+		data
+	}
+
+	/// Save the cache to a file, to `load()` on the next run.
+	pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+		fs::write(path, self.data())
+	}
+
+	pub(crate) fn cache(&self) -> VkPipelineCache {
+		self.0.cache
+	}
+}
+
+impl Drop for PipelineCacheContext {
+	fn drop(&mut self) {
+		let vk = self.vulkan.get();
+
+		unsafe {
+			(vk.drop_pipeline_cache)(vk.device, self.cache, null());
+		}
+	}
+}
+
+/// Check that `data` has a version one header for this GPU and driver.
+fn valid(vulkan: &Gpu, data: &[u8]) -> bool {
+	if data.len() < HEADER_SIZE {
+		return false;
+	}
+
+	let u32_at = |i: usize| {
+		data[i] as u32 | (data[i + 1] as u32) << 8
+			| (data[i + 2] as u32) << 16 | (data[i + 3] as u32) << 24
+	};
+	let properties = &vulkan.get().properties;
+
+	u32_at(0) as usize >= HEADER_SIZE
+		&& u32_at(4) == 1 // VK_PIPELINE_CACHE_HEADER_VERSION_ONE
+		&& u32_at(8) == properties.vendor_id
+		&& u32_at(12) == properties.device_id
+		&& data[16..32] == properties.pipeline_cache_uuid
+}
@@ -0,0 +1,382 @@
+// "asi_vulkan" - Aldaron's System Interface - Vulkan
+//
+// Copyright Jeron A. Lau 2018.
+// Distributed under the Boost Software License, Version 1.0.
+// (See accompanying file LICENSE_1_0.txt or copy at
+// https://www.boost.org/LICENSE_1_0.txt)
+
+use null;
+use mem;
+use c_void;
+
+use Gpu;
+use types::*;
+use std::{ rc::Rc, cell::{ Cell, RefCell } };
+
+/// The kind of queries in a `QueryPool`.
+#[derive(Copy, Clone, Debug, PartialEq)] pub enum QueryKind {
+	/// GPU timestamps, written with `cmd_write_timestamp()`.
+	Timestamp,
+	/// The number of samples that pass the depth test between
+	/// `cmd_begin_query()` and `cmd_end_query()`.
+	Occlusion,
+	/// `PipelineStatistics` counted between `cmd_begin_query()` and
+	/// `cmd_end_query()`.  Needs `Gpu::pipeline_statistics()`.
+	PipelineStatistics,
+}
+
+impl QueryKind {
+	/// The number of u64s in the result of one query.
+	fn nvalues(&self) -> usize {
+		match *self {
+			QueryKind::PipelineStatistics => 11,
+			_ => 1,
+		}
+	}
+}
+
+/// Counts of work done by the GPU, from a `QueryKind::PipelineStatistics`
+/// query.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct PipelineStatistics {
+	pub input_vertices: u64,
+	pub input_primitives: u64,
+	pub vertex_invocations: u64,
+	pub geometry_invocations: u64,
+	pub geometry_primitives: u64,
+	pub clipping_invocations: u64,
+	pub clipping_primitives: u64,
+	pub fragment_invocations: u64,
+	pub tessellation_control_patches: u64,
+	pub tessellation_evaluation_invocations: u64,
+	pub compute_invocations: u64,
+}
+
+/// A pool of queries of one `QueryKind`.  Queries must be reset with
+/// `cmd_reset_queries()` before they're written.
+#[derive(Clone)] pub struct QueryPool(Rc<QueryPoolContext>);
+
+struct QueryPoolContext {
+	pool: VkQueryPool,
+	kind: QueryKind,
+	count: u32,
+	vulkan: Gpu,
+}
+
+impl QueryPool {
+	/// Create a pool of `count` queries of `kind`.
+	pub fn new(vulkan: &Gpu, kind: QueryKind, count: u32) -> Self {
+		assert!(kind != QueryKind::Timestamp || vulkan.timestamps(),
+			"GPU doesn't support timestamps");
+		assert!(kind != QueryKind::PipelineStatistics
+			|| vulkan.pipeline_statistics(),
+			"GPU doesn't support pipeline statistics");
+
+		let connection = vulkan.get();
+		let mut pool = 0;
+
+		unsafe {
+			(connection.new_querypool)(
+				connection.device,
+				&VkQueryPoolCreateInfo {
+					s_type: VkStructureType::QueryPoolCreateInfo,
+					next: null(),
+					flags: 0,
+					query_type: match kind {
+						QueryKind::Timestamp =>
+							VkQueryType::Timestamp,
+						QueryKind::Occlusion =>
+							VkQueryType::Occlusion,
+						QueryKind::PipelineStatistics =>
+							VkQueryType::PipelineStatistics,
+					},
+					query_count: count,
+					// All 11 statistics, in `PipelineStatistics`
+					// order.
+					pipeline_statistics: match kind {
+						QueryKind::PipelineStatistics => 0x7FF,
+						_ => 0,
+					},
+				},
+				null(),
+				&mut pool
+			).unwrap();
+		}
+
+		QueryPool(Rc::new(QueryPoolContext {
+			pool, kind, count, vulkan: vulkan.clone(),
+		}))
+	}
+
+	/// Get the `VkQueryPool`.
+	pub fn pool(&self) -> VkQueryPool {
+		self.0.pool
+	}
+
+	/// Get the kind of queries in this pool.
+	pub fn kind(&self) -> QueryKind {
+		self.0.kind
+	}
+
+	/// Get the number of queries in this pool.
+	pub fn len(&self) -> u32 {
+		self.0.count
+	}
+
+	/// Check if this pool has no queries.
+	pub fn is_empty(&self) -> bool {
+		self.0.count == 0
+	}
+
+	/// Get the raw results of queries `first` to `first + count`, or `None`
+	/// if the GPU hasn't finished writing all of them yet.  Doesn't block.
+	pub fn results(&self, first: u32, count: u32) -> Option<Vec<u64>> {
+		let nvalues = self.0.kind.nvalues();
+		let mut results = vec![0u64; count as usize * nvalues];
+
+		if self.get(first, count, &mut results, nvalues, 0) {
+			Some(results)
+		} else {
+			None
+		}
+	}
+
+	/// Get the raw results of queries `first` to `first + count`, each
+	/// `None` if the GPU hasn't finished writing it yet.  Doesn't block.
+	pub fn available_results(&self, first: u32, count: u32)
+		-> Vec<Option<Vec<u64>>>
+	{
+		let nvalues = self.0.kind.nvalues();
+		let mut results = vec![0u64; count as usize * (nvalues + 1)];
+
+		self.get(first, count, &mut results, nvalues + 1,
+			VkQueryResult::WithAvailability as VkFlags);
+
+		results.chunks(nvalues + 1).map(|result| {
+			if result[nvalues] != 0 {
+				Some(result[..nvalues].to_vec())
+			} else {
+				None
+			}
+		}).collect()
+	}
+
+	/// Get the number of samples that passed in occlusion queries `first`
+	/// to `first + count`, each `None` if not available yet.  Doesn't
+	/// block.
+	pub fn occlusion(&self, first: u32, count: u32) -> Vec<Option<u64>> {
+		assert!(self.0.kind == QueryKind::Occlusion,
+			"Not an occlusion query pool");
+
+		self.available_results(first, count).into_iter()
+			.map(|result| result.map(|r| r[0]))
+			.collect()
+	}
+
+	/// Get pipeline statistics queries `first` to `first + count`, or
+	/// `None` if not all available yet.  Doesn't block.
+	pub fn statistics(&self, first: u32, count: u32)
+		-> Option<Vec<PipelineStatistics>>
+	{
+		assert!(self.0.kind == QueryKind::PipelineStatistics,
+			"Not a pipeline statistics query pool");
+
+		self.results(first, count).map(|results| {
+			results.chunks(11).map(|r| PipelineStatistics {
+				input_vertices: r[0],
+				input_primitives: r[1],
+				vertex_invocations: r[2],
+				geometry_invocations: r[3],
+				geometry_primitives: r[4],
+				clipping_invocations: r[5],
+				clipping_primitives: r[6],
+				fragment_invocations: r[7],
+				tessellation_control_patches: r[8],
+				tessellation_evaluation_invocations: r[9],
+				compute_invocations: r[10],
+			}).collect()
+		})
+	}
+
+	/// Read results of `count` queries, `stride` u64s each, into `results`.
+	/// Returns false if not all of them are available.
+	fn get(&self, first: u32, count: u32, results: &mut [u64],
+		stride: usize, flags: VkFlags) -> bool
+	{
+		assert!(first.checked_add(count)
+			.is_some_and(|end| end <= self.0.count),
+			"Query out of range");
+
+		let connection = self.0.vulkan.get();
+
+		let result = unsafe {
+			(connection.query_results)(
+				connection.device,
+				self.0.pool,
+				first,
+				count,
+				mem::size_of_val(&results[..]),
+				results.as_mut_ptr() as *mut c_void,
+				(stride * mem::size_of::<u64>()) as u64,
+				VkQueryResult::Result64Bit as VkFlags | flags,
+			)
+		};
+
+		match result {
+			VkResult::Success => true,
+			VkResult::NotReady => false,
+			result => {
+				result.unwrap();
+				false
+			}
+		}
+	}
+
+	/// Get timestamps `first` to `first + count` in nanoseconds, or `None`
+	/// if the GPU hasn't written all of them yet.  Only differences
+	/// between timestamps are meaningful.
+	pub fn timestamps(&self, first: u32, count: u32) -> Option<Vec<f64>> {
+		assert!(self.0.kind == QueryKind::Timestamp,
+			"Not a timestamp query pool");
+
+		let (bits, period) = {
+			let connection = self.0.vulkan.get();
+
+			(connection.timestamp_bits,
+				connection.properties.limits.timestamp_period)
+		};
+		let mask = if bits >= 64 { !0 } else { (1u64 << bits) - 1 };
+
+		self.results(first, count).map(|results| {
+			results.iter()
+				.map(|t| (t & mask) as f64 * period as f64)
+				.collect()
+		})
+	}
+}
+
+impl Drop for QueryPoolContext {
+	fn drop(&mut self) {
+		let vk = self.vulkan.get();
+
+		unsafe {
+			(vk.drop_querypool)(vk.device, self.pool, null());
+		}
+	}
+}
+
+/// The GPU time taken by a zone of a frame recorded with a `Profiler`.
+#[derive(Copy, Clone, Debug, PartialEq)] pub struct ZoneTime {
+	pub name: &'static str,
+	/// Milliseconds between the start and end of the zone.
+	pub ms: f64,
+}
+
+/// Records named GPU zones per frame, to see how long each pass takes on the
+/// GPU.  Uses one timestamp `QueryPool` for each frame in flight.
+pub struct Profiler {
+	pools: Vec<QueryPool>,
+	names: RefCell<Vec<Vec<&'static str>>>,
+	frame: Cell<usize>,
+	max_zones: u32,
+	times: Vec<ZoneTime>,
+}
+
+impl Profiler {
+	/// Create a profiler for up to `max_zones` zones per frame, with
+	/// `nframes` frames in flight.
+	pub fn new(vulkan: &Gpu, max_zones: u32, nframes: u32) -> Self {
+		assert!(nframes != 0, "Need at least one frame");
+
+		Profiler {
+			pools: (0..nframes).map(|_| {
+				QueryPool::new(vulkan, QueryKind::Timestamp,
+					max_zones * 2)
+			}).collect(),
+			names: RefCell::new(vec![vec![]; nframes as usize]),
+			frame: Cell::new(0),
+			max_zones,
+			times: vec![],
+		}
+	}
+
+	/// Start a new frame in the current command buffer, outside of a
+	/// render pass.  Collects the zone times of the frame that last used
+	/// this frame's query pool, if the GPU has finished it.
+	///
+	/// # Safety
+	/// The `Gpu`'s command buffer must be recording, outside of a render pass.
+	pub unsafe fn begin_frame(&mut self, connection: &Gpu) {
+		let frame = (self.frame.get() + 1) % self.pools.len();
+		let pool = &self.pools[frame];
+		let mut names = self.names.borrow_mut();
+		let names = &mut names[frame];
+
+		if !names.is_empty() {
+			let count = names.len() as u32 * 2;
+
+			if let Some(ts) = pool.timestamps(0, count) {
+				self.times = names.iter().enumerate()
+					.map(|(i, &name)| ZoneTime {
+						name,
+						ms: (ts[i * 2 + 1] - ts[i * 2])
+							/ 1_000_000.0,
+					}).collect();
+			}
+		}
+
+		names.clear();
+		::cmd_reset_queries(connection, pool, 0, pool.len());
+		self.frame.set(frame);
+	}
+
+	/// Start a zone called `name`, which ends when the returned `GpuZone`
+	/// is dropped.  Zones may be nested.
+	///
+	/// # Safety
+	/// `begin_frame()` must have been recorded in the `Gpu`'s command buffer,
+	/// which must still be recording when the `GpuZone` is dropped.
+	pub unsafe fn zone<'a>(&'a self, connection: &'a Gpu,
+		name: &'static str) -> GpuZone<'a>
+	{
+		let frame = self.frame.get();
+		let query = {
+			let mut names = self.names.borrow_mut();
+			let names = &mut names[frame];
+
+			assert!((names.len() as u32) < self.max_zones,
+				"Too many profiler zones in one frame");
+
+			names.push(name);
+			(names.len() as u32 - 1) * 2
+		};
+
+		::cmd_write_timestamp(connection, &self.pools[frame], query,
+			VkPipelineStage::TopOfPipe);
+
+		GpuZone { profiler: self, connection, query: query + 1 }
+	}
+
+	/// Get the zone times of the most recently collected frame.
+	pub fn times(&self) -> &[ZoneTime] {
+		&self.times
+	}
+}
+
+/// A zone started by `Profiler::zone()`, which ends when dropped.
+pub struct GpuZone<'a> {
+	profiler: &'a Profiler,
+	connection: &'a Gpu,
+	query: u32,
+}
+
+impl<'a> Drop for GpuZone<'a> {
+	fn drop(&mut self) {
+		let pool = &self.profiler.pools[self.profiler.frame.get()];
+
+		unsafe {
+			::cmd_write_timestamp(self.connection, pool, self.query,
+				VkPipelineStage::BottomOfPipe);
+		}
+	}
+}
@@ -0,0 +1,150 @@
+// "asi_vulkan" - Aldaron's System Interface - Vulkan
+//
+// Copyright Jeron A. Lau 2018.
+// Distributed under the Boost Software License, Version 1.0.
+// (See accompanying file LICENSE_1_0.txt or copy at
+// https://www.boost.org/LICENSE_1_0.txt)
+
+use types::*;
+
+/// How vertices are assembled into primitives.
+#[derive(Copy, Clone, Debug, PartialEq)] pub enum Topology {
+	/// Each vertex is a point.
+	Points,
+	/// Each 2 vertices are a line.
+	Lines,
+	/// Each vertex after the first continues a line from the last.
+	LineStrip,
+	/// Each 3 vertices are a triangle.
+	Triangles,
+	/// Each vertex after the first two makes a triangle with the last two.
+	TriangleStrip,
+	/// Each vertex after the first two makes a triangle with the last one
+	/// and the first one.
+	TriangleFan,
+	/// Each `n` vertices are the control points of a patch, for
+	/// tessellation (`StyleBuilder::tessellation()`).
+	Patches(u32),
+}
+
+impl Topology {
+	/// Whether or not this is a list (which can't use primitive restart).
+	pub(crate) fn list(&self) -> bool {
+		matches!(*self, Topology::Points | Topology::Lines
+			| Topology::Triangles | Topology::Patches(_))
+	}
+
+	pub(crate) fn vk(&self) -> VkPrimitiveTopology {
+		match *self {
+			Topology::Points => VkPrimitiveTopology::PointList,
+			Topology::Lines => VkPrimitiveTopology::LineList,
+			Topology::LineStrip => VkPrimitiveTopology::LineStrip,
+			Topology::Triangles => VkPrimitiveTopology::TriangleList,
+			Topology::TriangleStrip =>
+				VkPrimitiveTopology::TriangleStrip,
+			Topology::TriangleFan => VkPrimitiveTopology::TriangleFan,
+			Topology::Patches(_) => VkPrimitiveTopology::PatchList,
+		}
+	}
+}
+
+/// Which faces of triangles aren't drawn.
+#[derive(Copy, Clone, Debug, PartialEq)] pub enum CullMode {
+	None,
+	Front,
+	Back,
+	FrontAndBack,
+}
+
+impl CullMode {
+	pub(crate) fn vk(&self) -> VkCullMode {
+		match *self {
+			CullMode::None => VkCullMode::None,
+			CullMode::Front => VkCullMode::Front,
+			CullMode::Back => VkCullMode::Back,
+			CullMode::FrontAndBack => VkCullMode::FrontAndBack,
+		}
+	}
+}
+
+/// Which winding order of a triangle's vertices faces the front.
+#[derive(Copy, Clone, Debug, PartialEq)] pub enum FrontFace {
+	CounterClockwise,
+	Clockwise,
+}
+
+impl FrontFace {
+	pub(crate) fn vk(&self) -> VkFrontFace {
+		match *self {
+			FrontFace::CounterClockwise => VkFrontFace::CounterClockwise,
+			FrontFace::Clockwise => VkFrontFace::Clockwise,
+		}
+	}
+}
+
+/// How triangles are filled in.
+#[derive(Copy, Clone, Debug, PartialEq)] pub enum PolygonMode {
+	/// Fill the whole triangle.
+	Fill,
+	/// Draw only the edges (wireframe).
+	Line,
+	/// Draw only the vertices.
+	Point,
+}
+
+impl PolygonMode {
+	pub(crate) fn vk(&self) -> VkPolygonMode {
+		match *self {
+			PolygonMode::Fill => VkPolygonMode::Fill,
+			PolygonMode::Line => VkPolygonMode::Line,
+			PolygonMode::Point => VkPolygonMode::Point,
+		}
+	}
+}
+
+/// How a new depth value is compared with the one already in the depth
+/// buffer; the fragment is drawn if the comparison is true.
+#[derive(Copy, Clone, Debug, PartialEq)] pub enum CompareOp {
+	Never,
+	Less,
+	Equal,
+	LessOrEqual,
+	Greater,
+	NotEqual,
+	GreaterOrEqual,
+	Always,
+}
+
+impl CompareOp {
+	pub(crate) fn vk(&self) -> VkCompareOp {
+		match *self {
+			CompareOp::Never => VkCompareOp::Never,
+			CompareOp::Less => VkCompareOp::Less,
+			CompareOp::Equal => VkCompareOp::Equal,
+			CompareOp::LessOrEqual => VkCompareOp::LessOrEqual,
+			CompareOp::Greater => VkCompareOp::Greater,
+			CompareOp::NotEqual => VkCompareOp::NotEqual,
+			CompareOp::GreaterOrEqual => VkCompareOp::GreaterOrEqual,
+			CompareOp::Always => VkCompareOp::Always,
+		}
+	}
+}
+
+/// An offset added to the depth of each fragment, to draw decals on top of
+/// the surface they're on without z-fighting.
+#[derive(Copy, Clone, Debug, PartialEq)] pub struct DepthBias {
+	/// Constant offset, in units of the smallest depth difference.
+	pub constant: f32,
+	/// Offset scaled by the slope of the triangle's depth.
+	pub slope: f32,
+	/// Maximum (or minimum, if negative) offset, 0.0 for no limit.  Needs
+	/// the GPU to support depth bias clamping if not 0.0.
+	pub clamp: f32,
+}
+
+impl DepthBias {
+	/// Create a depth bias without a clamp.
+	pub fn new(constant: f32, slope: f32) -> Self {
+		DepthBias { constant, slope, clamp: 0.0 }
+	}
+}
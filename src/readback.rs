@@ -0,0 +1,380 @@
+// "asi_vulkan" - Aldaron's System Interface - Vulkan
+//
+// Copyright Jeron A. Lau 2018.
+// Distributed under the Boost Software License, Version 1.0.
+// (See accompanying file LICENSE_1_0.txt or copy at
+// https://www.boost.org/LICENSE_1_0.txt)
+
+use null;
+use std::{ fs::File, io::{ self, Write }, path::Path, slice };
+
+use Gpu;
+use Fence;
+use RenderTarget;
+use Buffer;
+use BufferBuilderType;
+use CommandPool;
+use types::*;
+
+/// Read back the pixels of a swapchain image as tightly packed RGBA8.  Call
+/// after the frame's fence has been waited on, before presenting.
+///
+/// # Safety
+/// `image` must be a swapchain image of `vulkan` in the present layout, that
+/// nothing is drawing to.
+pub unsafe fn readback_swapchain(vulkan: &Gpu, image: VkImage) -> Vec<u8> {
+	assert!(vulkan.readable(), "Swapchain doesn't support readback");
+
+	let extent = vulkan.get().extent;
+	let format = vulkan.get().format.clone();
+
+	readback(vulkan, image, VkImageLayout::PresentSrc, extent, format)
+}
+
+/// Read back the pixels of color image `index` of a `RenderTarget` as
+/// tightly packed RGBA8.
+///
+/// # Safety
+/// Nothing may still be drawing to `target`.
+pub unsafe fn readback_target(vulkan: &Gpu, target: &RenderTarget,
+	index: usize) -> Vec<u8>
+{
+	let format = vulkan.get().format.clone();
+
+	readback(vulkan, target.color(index).image().0, VkImageLayout::General,
+		target.extent(), format)
+}
+
+unsafe fn readback(vulkan: &Gpu, image: VkImage, layout: VkImageLayout,
+	extent: VkExtent2D, format: VkFormat) -> Vec<u8>
+{
+	let size = extent.width as usize * extent.height as usize * 4;
+	let buffer = Buffer::new(vulkan, &vec![0u8; size],
+		BufferBuilderType::Readback);
+	let fence = Fence::new(vulkan);
+	// A one-shot command buffer, leaving the `Gpu`'s for the frame alone.
+	let command_pool = CommandPool::new(vulkan);
+	let command_buffer = command_pool.command_buffer();
+	let cmdbuf = command_buffer.buffer();
+
+	{
+		let connection = vulkan.get();
+
+		::begin_cmdbuff(&connection, cmdbuf);
+
+		(connection.pipeline_barrier)(
+			cmdbuf,
+			VkPipelineStage::AllCommands,
+			VkPipelineStage::Transfer,
+			0, 0, null(), 0, null(), 1,
+			&color_barrier(image, VkAccess::MemoryWriteBit,
+				VkAccess::TransferReadBit, layout,
+				VkImageLayout::TransferSrcOptimal)
+		);
+
+		(connection.copy_image_to_buffer)(
+			cmdbuf,
+			image,
+			VkImageLayout::TransferSrcOptimal,
+			buffer.buffer(),
+			1,
+			&VkBufferImageCopy {
+				buffer_offset: 0,
+				buffer_row_length: 0, // tightly packed
+				buffer_image_height: 0,
+				image_subresource: VkImageSubresourceLayers {
+					aspect_mask: VkImageAspectFlags::Color,
+					mip_level: 0,
+					base_array_layer: 0,
+					layer_count: 1,
+				},
+				image_offset: VkOffset3D { x: 0, y: 0, z: 0 },
+				image_extent: VkExtent3D {
+					width: extent.width,
+					height: extent.height,
+					depth: 1,
+				},
+			}
+		);
+
+		// Put the image back how it was, and make the copy visible to
+		// the CPU.
+		(connection.pipeline_barrier)(
+			cmdbuf,
+			VkPipelineStage::Transfer,
+			VkPipelineStage::AllCommands,
+			0, 0, null(), 0, null(), 1,
+			&color_barrier(image, VkAccess::TransferReadBit,
+				VkAccess::MemoryReadBit,
+				VkImageLayout::TransferSrcOptimal, layout)
+		);
+		(connection.pipeline_barrier)(
+			cmdbuf,
+			VkPipelineStage::Transfer,
+			VkPipelineStage::Host,
+			0, 0, null(), 1,
+			&VkBufferMemoryBarrier {
+				s_type: VkStructureType::BufferMemoryBarrier,
+				p_next: null(),
+				src_access_mask: VkAccess::TransferWriteBit
+					as VkFlags,
+				dst_access_mask: VkAccess::HostReadBit
+					as VkFlags,
+				src_queue_family_index: !0,
+				dst_queue_family_index: !0,
+				buffer: buffer.buffer(),
+				offset: 0,
+				size: !0,
+			},
+			0, null()
+		);
+	}
+
+	{
+		let connection = vulkan.get();
+
+		(connection.end_cmdbuff)(cmdbuf).unwrap();
+		::submit(&connection, cmdbuf, &fence, VkPipelineStage::Transfer,
+			None);
+	}
+	::wait_fence(vulkan, &fence);
+
+	let mapped: *mut u8 = ::map_memory(vulkan, buffer.memory(),
+		size as u64);
+	let mut pixels = slice::from_raw_parts(mapped, size).to_vec();
+	::unmap_memory(vulkan, buffer.memory());
+
+	match format {
+		VkFormat::B8g8r8a8Unorm | VkFormat::B8g8r8a8Snorm
+		| VkFormat::B8g8r8a8Uscaled | VkFormat::B8g8r8a8Sscaled
+		| VkFormat::B8g8r8a8Uint | VkFormat::B8g8r8a8Sint
+		| VkFormat::B8g8r8a8Srgb =>
+		{
+			for pixel in pixels.chunks_mut(4) {
+				pixel.swap(0, 2);
+			}
+		}
+		VkFormat::R8g8b8a8Unorm | VkFormat::R8g8b8a8Snorm
+		| VkFormat::R8g8b8a8Uscaled | VkFormat::R8g8b8a8Sscaled
+		| VkFormat::R8g8b8a8Uint | VkFormat::R8g8b8a8Sint
+		| VkFormat::R8g8b8a8Srgb | VkFormat::A8b8g8r8UnormPack32
+		| VkFormat::A8b8g8r8SnormPack32
+		| VkFormat::A8b8g8r8UscaledPack32
+		| VkFormat::A8b8g8r8SscaledPack32
+		| VkFormat::A8b8g8r8UintPack32
+		| VkFormat::A8b8g8r8SintPack32
+		| VkFormat::A8b8g8r8SrgbPack32 => { /* already RGBA */ }
+		format => panic!("Can't read back format {:?}", format),
+	}
+
+	pixels
+}
+
+fn color_barrier(image: VkImage, src_access_mask: VkAccess,
+	dst_access_mask: VkAccess, old_layout: VkImageLayout,
+	new_layout: VkImageLayout) -> VkImageMemoryBarrier
+{
+	VkImageMemoryBarrier {
+		s_type: VkStructureType::ImageMemoryBarrier,
+		p_next: null(),
+		src_access_mask,
+		dst_access_mask,
+		old_layout,
+		new_layout,
+		src_queue_family_index: !0,
+		dst_queue_family_index: !0,
+		image,
+		subresource_range: VkImageSubresourceRange {
+			aspect_mask: VkImageAspectFlags::Color,
+			base_mip_level: 0,
+			level_count: 1,
+			base_array_layer: 0,
+			layer_count: 1,
+		},
+	}
+}
+
+/// Write tightly packed RGBA8 pixels to a binary PPM file (alpha is
+/// dropped).
+pub fn write_ppm<P: AsRef<Path>>(path: P, width: u32, height: u32,
+	rgba: &[u8]) -> io::Result<()>
+{
+	assert_eq!(rgba.len(), width as usize * height as usize * 4);
+
+	let mut file = File::create(path)?;
+
+	write!(file, "P6\n{} {}\n255\n", width, height)?;
+
+	let mut rgb = Vec::with_capacity(width as usize * height as usize * 3);
+	for pixel in rgba.chunks(4) {
+		rgb.extend_from_slice(&pixel[..3]);
+	}
+
+	file.write_all(&rgb)
+}
+
+/// Write tightly packed RGBA8 pixels to an (uncompressed) PNG file.
+pub fn write_png<P: AsRef<Path>>(path: P, width: u32, height: u32,
+	rgba: &[u8]) -> io::Result<()>
+{
+	let png = encode_png(width, height, rgba)?;
+
+	File::create(path)?.write_all(&png)
+}
+
+/// Encode tightly packed RGBA8 pixels as an (uncompressed) PNG.
+fn encode_png(width: u32, height: u32, rgba: &[u8]) -> io::Result<Vec<u8>> {
+	if width == 0 || height == 0 {
+		return Err(io::Error::new(io::ErrorKind::InvalidInput,
+			"PNG images can't be empty"));
+	}
+
+	assert_eq!(rgba.len(), width as usize * height as usize * 4);
+
+	// Scanlines, each with filter type 0 (None).
+	let mut raw = Vec::with_capacity(rgba.len() + height as usize);
+	for row in rgba.chunks(width as usize * 4) {
+		raw.push(0);
+		raw.extend_from_slice(row);
+	}
+
+	// zlib stream of stored (uncompressed) deflate blocks.
+	let mut zlib = vec![0x78, 0x01];
+	let mut blocks = raw.chunks(0xFFFF).peekable();
+	while let Some(block) = blocks.next() {
+		let len = block.len() as u16;
+		zlib.push(if blocks.peek().is_none() { 1 } else { 0 });
+		zlib.extend_from_slice(&[len as u8, (len >> 8) as u8]);
+		zlib.extend_from_slice(&[!len as u8, (!len >> 8) as u8]);
+		zlib.extend_from_slice(block);
+	}
+	zlib.extend_from_slice(&be32(adler32(&raw)));
+
+	let mut ihdr = vec![];
+	ihdr.extend_from_slice(&be32(width));
+	ihdr.extend_from_slice(&be32(height));
+	// 8-bit depth, RGBA, default compression, filter & no interlace.
+	ihdr.extend_from_slice(&[8, 6, 0, 0, 0]);
+
+	let mut png = vec![0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1A, b'\n'];
+
+	push_chunk(&mut png, b"IHDR", &ihdr);
+	push_chunk(&mut png, b"IDAT", &zlib);
+	push_chunk(&mut png, b"IEND", &[]);
+
+	Ok(png)
+}
+
+fn push_chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+	let mut crc_data = kind.to_vec();
+	crc_data.extend_from_slice(data);
+
+	png.extend_from_slice(&be32(data.len() as u32));
+	png.extend_from_slice(&crc_data);
+	png.extend_from_slice(&be32(crc32(&crc_data)));
+}
+
+fn crc32(data: &[u8]) -> u32 {
+	let mut crc = !0u32;
+
+	for byte in data {
+		crc ^= *byte as u32;
+		for _ in 0..8 {
+			crc = if crc & 1 != 0 {
+				(crc >> 1) ^ 0xEDB88320
+			} else {
+				crc >> 1
+			};
+		}
+	}
+
+	!crc
+}
+
+fn adler32(data: &[u8]) -> u32 {
+	let (mut a, mut b) = (1u32, 0u32);
+
+	for byte in data {
+		a = (a + *byte as u32) % 65521;
+		b = (b + a) % 65521;
+	}
+
+	(b << 16) | a
+}
+
+fn be32(value: u32) -> [u8; 4] {
+	[(value >> 24) as u8, (value >> 16) as u8, (value >> 8) as u8,
+		value as u8]
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn u32_at(data: &[u8], i: usize) -> u32 {
+		(data[i] as u32) << 24 | (data[i + 1] as u32) << 16
+			| (data[i + 2] as u32) << 8 | data[i + 3] as u32
+	}
+
+	/// Split a PNG into its chunks, checking each CRC.
+	fn chunks(png: &[u8]) -> Vec<([u8; 4], Vec<u8>)> {
+		assert_eq!(&png[..8], &[0x89, b'P', b'N', b'G', b'\r', b'\n',
+			0x1A, b'\n']);
+
+		let mut chunks = vec![];
+		let mut i = 8;
+
+		while i < png.len() {
+			let len = u32_at(png, i) as usize;
+			let kind = [png[i + 4], png[i + 5], png[i + 6], png[i + 7]];
+			let data = png[i + 8..i + 8 + len].to_vec();
+
+			assert_eq!(crc32(&png[i + 4..i + 8 + len]),
+				u32_at(png, i + 8 + len));
+			chunks.push((kind, data));
+			i += 12 + len;
+		}
+
+		chunks
+	}
+
+	#[test]
+	fn checksums() {
+		assert_eq!(adler32(b""), 1);
+		assert_eq!(adler32(b"Wikipedia"), 0x11E60398);
+		assert_eq!(crc32(b""), 0);
+		assert_eq!(crc32(b"123456789"), 0xCBF43926);
+		assert_eq!(crc32(b"IEND"), 0xAE426082);
+	}
+
+	#[test]
+	fn round_trip() {
+		let pixel = [0x12, 0x34, 0x56, 0x78];
+		let png = encode_png(1, 1, &pixel).unwrap();
+		let chunks = chunks(&png);
+
+		assert_eq!(chunks.len(), 3);
+		assert_eq!(&chunks[0].0, b"IHDR");
+		assert_eq!(chunks[0].1, vec![0, 0, 0, 1, 0, 0, 0, 1, 8, 6, 0, 0, 0]);
+		assert_eq!(&chunks[1].0, b"IDAT");
+		assert_eq!(&chunks[2].0, b"IEND");
+		assert!(chunks[2].1.is_empty());
+
+		// One final stored block holding the filter byte and pixel.
+		let zlib = &chunks[1].1;
+		let raw = [0, 0x12, 0x34, 0x56, 0x78];
+
+		assert_eq!(&zlib[..2], &[0x78, 0x01]);
+		assert_eq!((zlib[0] as u32 * 256 + zlib[1] as u32) % 31, 0);
+		assert_eq!(&zlib[2..7], &[1, 5, 0, !5, !0]);
+		assert_eq!(&zlib[7..12], &raw);
+		assert_eq!(u32_at(zlib, 12), adler32(&raw));
+		assert_eq!(zlib.len(), 16);
+	}
+
+	#[test]
+	fn empty() {
+		assert!(encode_png(0, 4, &[]).is_err());
+		assert!(encode_png(4, 0, &[]).is_err());
+	}
+}
@@ -0,0 +1,1055 @@
+// "asi_vulkan" - Aldaron's System Interface - Vulkan
+//
+// Copyright Jeron A. Lau 2018.
+// Distributed under the Boost Software License, Version 1.0.
+// (See accompanying file LICENSE_1_0.txt or copy at
+// https://www.boost.org/LICENSE_1_0.txt)
+
+use Stages;
+use Descriptor;
+use VertexFormat;
+use std::collections::HashMap;
+
+pub(crate) const MAGIC: u32 = 0x07230203;
+
+// Opcodes
+const OP_NAME: u32 = 5;
+const OP_MEMBER_NAME: u32 = 6;
+const OP_ENTRY_POINT: u32 = 15;
+const OP_TYPE_BOOL: u32 = 20;
+const OP_TYPE_INT: u32 = 21;
+const OP_TYPE_FLOAT: u32 = 22;
+const OP_TYPE_VECTOR: u32 = 23;
+const OP_TYPE_MATRIX: u32 = 24;
+const OP_TYPE_IMAGE: u32 = 25;
+const OP_TYPE_SAMPLER: u32 = 26;
+const OP_TYPE_SAMPLED_IMAGE: u32 = 27;
+const OP_TYPE_ARRAY: u32 = 28;
+const OP_TYPE_RUNTIME_ARRAY: u32 = 29;
+const OP_TYPE_STRUCT: u32 = 30;
+const OP_TYPE_POINTER: u32 = 32;
+const OP_CONSTANT: u32 = 43;
+const OP_VARIABLE: u32 = 59;
+const OP_DECORATE: u32 = 71;
+const OP_MEMBER_DECORATE: u32 = 72;
+
+// Decorations
+const BUFFER_BLOCK: u32 = 3;
+const ARRAY_STRIDE: u32 = 6;
+const MATRIX_STRIDE: u32 = 7;
+const BUILT_IN: u32 = 11;
+const LOCATION: u32 = 30;
+const BINDING: u32 = 33;
+const DESCRIPTOR_SET: u32 = 34;
+const OFFSET: u32 = 35;
+
+// Storage classes
+const UNIFORM_CONSTANT: u32 = 0;
+const INPUT: u32 = 1;
+const UNIFORM: u32 = 2;
+const OUTPUT: u32 = 3;
+const PUSH_CONSTANT: u32 = 9;
+const STORAGE_BUFFER: u32 = 12;
+
+// Image dimensions
+const DIM_BUFFER: u32 = 5;
+
+/// The scalar type of a shader variable.
+#[derive(Copy, Clone, Debug, PartialEq)] pub enum Scalar {
+	Bool,
+	Int,
+	Uint,
+	Float,
+}
+
+/// The type of a shader input or output variable.
+#[derive(Copy, Clone, Debug, PartialEq)] pub struct Type {
+	pub scalar: Scalar,
+	/// Bits per component.
+	pub width: u32,
+	/// Components per column (1 for scalars).
+	pub components: u32,
+	/// Columns (1 for scalars and vectors).
+	pub columns: u32,
+}
+
+impl Type {
+	/// Get the vertex attribute format of one column of this type, if it
+	/// can be read from a vertex buffer.
+	pub fn vertex_format(&self) -> Option<VertexFormat> {
+		match (self.scalar, self.width, self.components) {
+			(Scalar::Float, 32, 1) => Some(VertexFormat::Float),
+			(Scalar::Float, 32, 2) => Some(VertexFormat::Vec2),
+			(Scalar::Float, 32, 3) => Some(VertexFormat::Vec3),
+			(Scalar::Float, 32, 4) => Some(VertexFormat::Vec4),
+			(Scalar::Int, 32, 1) => Some(VertexFormat::Int),
+			(Scalar::Int, 32, 4) => Some(VertexFormat::Ivec4),
+			(Scalar::Uint, 32, 1) => Some(VertexFormat::Uint),
+			(Scalar::Uint, 32, 4) => Some(VertexFormat::Uvec4),
+			_ => None,
+		}
+	}
+}
+
+/// A shader input or output variable with a location.
+#[derive(Clone, Debug, PartialEq)] pub struct Interface {
+	pub location: u32,
+	pub name: String,
+	pub ty: Type,
+	/// Array length, 1 if not an array.
+	pub count: u32,
+}
+
+impl Interface {
+	/// The number of locations this variable takes.
+	pub fn nlocations(&self) -> u32 {
+		self.ty.columns * self.count
+	}
+}
+
+/// An entry point of a shader module, and its inputs and outputs.
+#[derive(Clone, Debug, PartialEq)] pub struct EntryPoint {
+	pub name: String,
+	pub stage: Stages,
+	/// Inputs (not including built-ins), sorted by location.
+	pub inputs: Vec<Interface>,
+	/// Outputs (not including built-ins), sorted by location.
+	pub outputs: Vec<Interface>,
+}
+
+/// A descriptor used by a shader module.
+#[derive(Clone, Debug, PartialEq)] pub struct DescriptorBinding {
+	pub set: u32,
+	pub binding: u32,
+	pub name: String,
+	pub descriptor: Descriptor,
+	/// Array length, 1 if not an array.
+	pub count: u32,
+}
+
+/// What a SPIR-V shader module declares, found by parsing it.
+#[derive(Clone, Debug, Default, PartialEq)] pub struct Reflection {
+	pub entry_points: Vec<EntryPoint>,
+	/// Sorted by set, then binding.
+	pub descriptors: Vec<DescriptorBinding>,
+	/// Size in bytes of the push constant block, 0 if there isn't one.
+	pub push_constants: u32,
+}
+
+impl Reflection {
+	/// Get the entry point for `stage`, if there is one.
+	pub fn entry_point(&self, stage: Stages) -> Option<&EntryPoint> {
+		self.entry_points.iter().find(|e| e.stage == stage)
+	}
+}
+
+/// Get a description of each fragment input that doesn't match an output of
+/// the `previous` stage (the vertex, tessellation evaluation or geometry
+/// shader, whichever runs last before the fragment shader) at the same
+/// location.
+pub fn interface_mismatches(previous: &EntryPoint, fragment: &EntryPoint)
+	-> Vec<String>
+{
+	let stage = previous.stage.name();
+	let mut mismatches = vec![];
+
+	for input in fragment.inputs.iter() {
+		match previous.outputs.iter()
+			.find(|o| o.location == input.location)
+		{
+			None => mismatches.push(format!(
+				"Fragment input `{}` at location {} isn't \
+				written by the {} shader", input.name,
+				input.location, stage)),
+			Some(output) if output.ty != input.ty
+				|| output.count != input.count =>
+			{
+				mismatches.push(format!(
+					"Fragment input `{}` ({:?} x{}) doesn't \
+					match {} output `{}` ({:?} x{}) at \
+					location {}", input.name, input.ty,
+					input.count, stage, output.name,
+					output.ty, output.count,
+					input.location))
+			}
+			_ => {}
+		}
+	}
+
+	mismatches
+}
+
+#[derive(Clone)] enum Ty {
+	Bool,
+	Int(u32, bool),
+	Float(u32),
+	Vector(u32, u32),
+	Matrix(u32, u32),
+	Image(u32, u32),
+	Sampler,
+	SampledImage,
+	Array(u32, u32),
+	RuntimeArray(u32),
+	Struct(Vec<u32>),
+	Pointer(u32),
+}
+
+struct Module {
+	names: HashMap<u32, String>,
+	member_names: HashMap<(u32, u32), String>,
+	types: HashMap<u32, Ty>,
+	constants: HashMap<u32, u32>,
+	decorations: HashMap<(u32, u32), u32>,
+	member_decorations: HashMap<(u32, u32, u32), u32>,
+}
+
+impl Module {
+	fn decoration(&self, id: u32, decoration: u32) -> Option<u32> {
+		self.decorations.get(&(id, decoration)).cloned()
+	}
+
+	fn name(&self, id: u32) -> String {
+		self.names.get(&id).cloned().unwrap_or_default()
+	}
+
+	fn ty(&self, id: u32) -> Result<&Ty, String> {
+		self.types.get(&id).ok_or_else(|| format!("Unknown type {}", id))
+	}
+
+	/// Strip arrays from a type, returning the element type and length.
+	fn element(&self, id: u32) -> Result<(u32, u32), String> {
+		match *self.ty(id)? {
+			Ty::Array(element, length) => {
+				let length = *self.constants.get(&length)
+					.ok_or("Array length isn't a constant")?;
+
+				Ok((element, length))
+			}
+			// Unbounded, count as 1.
+			Ty::RuntimeArray(element) => Ok((element, 1)),
+			_ => Ok((id, 1)),
+		}
+	}
+
+	fn interface_type(&self, id: u32) -> Result<Type, String> {
+		Ok(match *self.ty(id)? {
+			Ty::Bool => Type {
+				scalar: Scalar::Bool, width: 32, components: 1,
+				columns: 1,
+			},
+			Ty::Int(width, signed) => Type {
+				scalar: if signed { Scalar::Int }
+					else { Scalar::Uint },
+				width, components: 1, columns: 1,
+			},
+			Ty::Float(width) => Type {
+				scalar: Scalar::Float, width, components: 1,
+				columns: 1,
+			},
+			Ty::Vector(component, n) => Type {
+				components: n, ..self.interface_type(component)?
+			},
+			Ty::Matrix(column, n) => Type {
+				columns: n, ..self.interface_type(column)?
+			},
+			_ => return Err("Unsupported interface type".to_string()),
+		})
+	}
+
+	/// Get the interface of each member of the struct `id`, numbering
+	/// members without a location on from `location` (of the variable).
+	fn block(&self, id: u32, members: &[u32], location: Option<u32>,
+		name: &str) -> Result<Vec<Interface>, String>
+	{
+		let mut next = location;
+		let mut interfaces = vec![];
+
+		for (i, member) in members.iter().enumerate() {
+			let i = i as u32;
+			let member_name = self.member_names.get(&(id, i))
+				.cloned().unwrap_or_default();
+			let location = self.member_decorations
+				.get(&(id, i, LOCATION)).cloned().or(next)
+				.ok_or_else(|| format!("Member {} (`{}`) has no \
+					location", i, member_name))?;
+			let (element, count) = self.element(*member)?;
+			let interface = Interface {
+				location, count,
+				name: if name.is_empty() {
+					member_name
+				} else {
+					format!("{}.{}", name, member_name)
+				},
+				ty: self.interface_type(element)?,
+			};
+
+			next = Some(location + interface.nlocations());
+			interfaces.push(interface);
+		}
+
+		Ok(interfaces)
+	}
+
+	/// Size of a type in a buffer block.  `matrix_stride` is from the
+	/// member decoration, if any.
+	fn size(&self, id: u32, matrix_stride: Option<u32>)
+		-> Result<u32, String>
+	{
+		Ok(match *self.ty(id)? {
+			Ty::Bool => 4,
+			Ty::Int(width, _) | Ty::Float(width) => width / 8,
+			Ty::Vector(component, n) => n * self.size(component, None)?,
+			Ty::Matrix(column, n) => n * match matrix_stride {
+				Some(stride) => stride,
+				None => self.size(column, None)?,
+			},
+			Ty::Array(element, _) => {
+				let (_, length) = self.element(id)?;
+				let stride = match self.decoration(id, ARRAY_STRIDE) {
+					Some(stride) => stride,
+					None => self.size(element, matrix_stride)?,
+				};
+
+				length * stride
+			}
+			Ty::RuntimeArray(_) => 0,
+			Ty::Struct(ref members) => {
+				let mut size = 0;
+
+				for (i, member) in members.iter().enumerate() {
+					let i = i as u32;
+					let offset = self.member_decorations
+						.get(&(id, i, OFFSET)).cloned()
+						.unwrap_or(size);
+					let stride = self.member_decorations
+						.get(&(id, i, MATRIX_STRIDE)).cloned();
+
+					size = size.max(offset
+						+ self.size(*member, stride)?);
+				}
+
+				size
+			}
+			_ => return Err("Unsupported buffer type".to_string()),
+		})
+	}
+
+	/// Whether or not a struct has built-in members (like gl_PerVertex).
+	fn builtin_block(&self, id: u32) -> bool {
+		match self.types.get(&id) {
+			Some(Ty::Struct(members)) => (0..members.len())
+				.any(|i| self.member_decorations
+					.contains_key(&(id, i as u32, BUILT_IN))),
+			_ => false,
+		}
+	}
+
+	fn descriptor(&self, id: u32, storage: u32)
+		-> Result<Descriptor, String>
+	{
+		Ok(match (storage, self.ty(id)?) {
+			(STORAGE_BUFFER, _) => Descriptor::Storage,
+			(UNIFORM, _) if self.decoration(id, BUFFER_BLOCK)
+				.is_some() => Descriptor::Storage,
+			(UNIFORM, _) => Descriptor::Uniform,
+			(_, &Ty::SampledImage) => Descriptor::Sampler,
+			(_, &Ty::Image(DIM_BUFFER, 2)) => Descriptor::StorageTexel,
+			(_, &Ty::Image(DIM_BUFFER, _)) => Descriptor::UniformTexel,
+			(_, &Ty::Image(_, 2)) => Descriptor::StorageImage,
+			(_, &Ty::Image(..)) => return Err(
+				"Separate sampled images aren't supported, use a \
+				combined image sampler".to_string()),
+			(_, &Ty::Sampler) => return Err(
+				"Separate samplers aren't supported, use a \
+				combined image sampler".to_string()),
+			_ => return Err("Unsupported descriptor type".to_string()),
+		})
+	}
+}
+
+/// Read a nul-terminated string packed into words.
+fn string(words: &[u32]) -> (String, usize) {
+	let mut bytes = vec![];
+
+	for (i, word) in words.iter().enumerate() {
+		for shift in 0..4 {
+			let byte = (word >> (shift * 8)) as u8;
+
+			if byte == 0 {
+				return (String::from_utf8_lossy(&bytes)
+					.into_owned(), i + 1);
+			}
+
+			bytes.push(byte);
+		}
+	}
+
+	(String::from_utf8_lossy(&bytes).into_owned(), words.len())
+}
+
+fn stage(execution_model: u32) -> Option<Stages> {
+	Some(match execution_model {
+		0 => Stages::VERTEX,
+		1 => Stages::TESSELLATION_CONTROL,
+		2 => Stages::TESSELLATION_EVALUATION,
+		3 => Stages::GEOMETRY,
+		4 => Stages::FRAGMENT,
+		5 => Stages::COMPUTE,
+		_ => return None,
+	})
+}
+
+/// Check that `bytes` is SPIR-V, and copy it into words, swapping the byte
+/// order if it was written on a machine with the other endianness.
+pub(crate) fn words(bytes: &[u8]) -> Result<Vec<u32>, String> {
+	if !bytes.len().is_multiple_of(4) {
+		return Err("Length isn't a multiple of 4".to_string());
+	}
+	if bytes.len() < 20 {
+		return Err("Too short for a SPIR-V header".to_string());
+	}
+
+	let words: Vec<u32> = bytes.chunks(4).map(|b| {
+		b[0] as u32 | (b[1] as u32) << 8 | (b[2] as u32) << 16
+			| (b[3] as u32) << 24
+	}).collect();
+
+	if words[0] == MAGIC {
+		Ok(words)
+	} else if words[0].swap_bytes() == MAGIC {
+		Ok(words.iter().map(|w| w.swap_bytes()).collect())
+	} else {
+		Err(format!("Bad magic number {:#010x}", words[0]))
+	}
+}
+
+/// Get the stage and name of each entry point in the SPIR-V `words`, in
+/// the order they're declared.
+pub(crate) fn entry_points(words: &[u32]) -> Vec<(Stages, String)> {
+	let mut entry_points = vec![];
+	let mut i = 5;
+
+	while i < words.len() {
+		let count = (words[i] >> 16) as usize;
+
+		if count == 0 || i + count > words.len() {
+			break;
+		}
+
+		if words[i] & 0xFFFF == OP_ENTRY_POINT && count > 3 {
+			if let Some(stage) = stage(words[i + 1]) {
+				entry_points.push((stage,
+					string(&words[i + 3..i + count]).0));
+			}
+		}
+
+		i += count;
+	}
+
+	entry_points
+}
+
+/// Parse the SPIR-V `words` of a shader module.
+pub(crate) fn reflect(words: &[u32]) -> Result<Reflection, String> {
+	if words.len() < 5 || words[0] != MAGIC {
+		return Err("Not SPIR-V".to_string());
+	}
+
+	let mut module = Module {
+		names: HashMap::new(),
+		member_names: HashMap::new(),
+		types: HashMap::new(),
+		constants: HashMap::new(),
+		decorations: HashMap::new(),
+		member_decorations: HashMap::new(),
+	};
+	let mut entry_points = vec![];
+	let mut variables = vec![];
+	let mut i = 5;
+
+	while i < words.len() {
+		let opcode = words[i] & 0xFFFF;
+		let count = (words[i] >> 16) as usize;
+
+		if count == 0 || i + count > words.len() {
+			return Err("Truncated instruction".to_string());
+		}
+
+		let op = &words[i + 1..i + count];
+		let truncated = || format!("Truncated instruction {}", opcode);
+		let at = |n: usize| -> Result<u32, String> {
+			op.get(n).cloned().ok_or_else(truncated)
+		};
+		let from = |n: usize| -> Result<&[u32], String> {
+			op.get(n..).ok_or_else(truncated)
+		};
+
+		match opcode {
+			OP_NAME => {
+				module.names.insert(at(0)?, string(from(1)?).0);
+			}
+			OP_MEMBER_NAME => {
+				module.member_names.insert((at(0)?, at(1)?),
+					string(from(2)?).0);
+			}
+			OP_ENTRY_POINT => {
+				let operands = from(2)?;
+				let (name, len) = string(operands);
+
+				entry_points.push((at(0)?, name,
+					operands[len..].to_vec()));
+			}
+			OP_TYPE_BOOL => {
+				module.types.insert(at(0)?, Ty::Bool);
+			}
+			OP_TYPE_INT => {
+				module.types.insert(at(0)?,
+					Ty::Int(at(1)?, at(2)? != 0));
+			}
+			OP_TYPE_FLOAT => {
+				module.types.insert(at(0)?, Ty::Float(at(1)?));
+			}
+			OP_TYPE_VECTOR => {
+				module.types.insert(at(0)?,
+					Ty::Vector(at(1)?, at(2)?));
+			}
+			OP_TYPE_MATRIX => {
+				module.types.insert(at(0)?,
+					Ty::Matrix(at(1)?, at(2)?));
+			}
+			OP_TYPE_IMAGE => {
+				module.types.insert(at(0)?,
+					Ty::Image(at(2)?, at(6)?));
+			}
+			OP_TYPE_SAMPLER => {
+				module.types.insert(at(0)?, Ty::Sampler);
+			}
+			OP_TYPE_SAMPLED_IMAGE => {
+				module.types.insert(at(0)?, Ty::SampledImage);
+			}
+			OP_TYPE_ARRAY => {
+				module.types.insert(at(0)?,
+					Ty::Array(at(1)?, at(2)?));
+			}
+			OP_TYPE_RUNTIME_ARRAY => {
+				module.types.insert(at(0)?,
+					Ty::RuntimeArray(at(1)?));
+			}
+			OP_TYPE_STRUCT => {
+				module.types.insert(at(0)?,
+					Ty::Struct(from(1)?.to_vec()));
+			}
+			OP_TYPE_POINTER => {
+				module.types.insert(at(0)?, Ty::Pointer(at(2)?));
+			}
+			OP_CONSTANT => {
+				module.constants.insert(at(1)?, at(2)?);
+			}
+			OP_VARIABLE => {
+				variables.push((at(1)?, at(0)?, at(2)?));
+			}
+			OP_DECORATE => {
+				module.decorations.insert((at(0)?, at(1)?),
+					op.get(2).cloned().unwrap_or(0));
+			}
+			OP_MEMBER_DECORATE => {
+				module.member_decorations.insert(
+					(at(0)?, at(1)?, at(2)?),
+					op.get(3).cloned().unwrap_or(0));
+			}
+			_ => {}
+		}
+
+		i += count;
+	}
+
+	let mut reflection = Reflection::default();
+	let mut inputs = vec![];
+	let mut outputs = vec![];
+
+	for &(id, pointer, storage) in variables.iter() {
+		let ty = match *module.ty(pointer)? {
+			Ty::Pointer(ty) => ty,
+			_ => return Err("Variable isn't a pointer".to_string()),
+		};
+		let name = module.name(id);
+
+		match storage {
+			INPUT | OUTPUT => {
+				// Arrays of blocks are per-vertex inputs of
+				// geometry and tessellation shaders.
+				let (element, count) = module.element(ty)?;
+				let location = module.decoration(id, LOCATION);
+
+				if module.decoration(id, BUILT_IN).is_some()
+					|| module.builtin_block(element)
+				{
+					continue;
+				}
+
+				let interfaces = if let Ty::Struct(ref members)
+					= *module.ty(element)?
+				{
+					module.block(element, members, location,
+						&name)
+				} else if let Some(location) = location {
+					module.interface_type(element).map(|ty| {
+						vec![Interface {
+							location, name, count, ty,
+						}]
+					})
+				} else {
+					continue; // Built-in
+				};
+				let interfaces = interfaces.map_err(|e| {
+					format!("`{}`: {}", module.name(id), e)
+				})?;
+
+				for interface in interfaces {
+					if storage == INPUT {
+						inputs.push((id, interface));
+					} else {
+						outputs.push((id, interface));
+					}
+				}
+			}
+			UNIFORM_CONSTANT | UNIFORM | STORAGE_BUFFER => {
+				let (element, count) = module.element(ty)?;
+
+				reflection.descriptors.push(DescriptorBinding {
+					set: module.decoration(id, DESCRIPTOR_SET)
+						.unwrap_or(0),
+					binding: module.decoration(id, BINDING)
+						.unwrap_or(0),
+					descriptor: module.descriptor(element,
+						storage).map_err(|e| {
+							format!("`{}`: {}", name, e)
+						})?,
+					name, count,
+				});
+			}
+			PUSH_CONSTANT => {
+				reflection.push_constants = module.size(ty, None)?;
+			}
+			_ => {}
+		}
+	}
+
+	reflection.descriptors.sort_by_key(|d| (d.set, d.binding));
+
+	for (model, name, interface) in entry_points {
+		let stage = match stage(model) {
+			Some(stage) => stage,
+			None => continue, // Kernels
+		};
+		let filter = |vars: &[(u32, Interface)]| {
+			let mut vars: Vec<Interface> = vars.iter()
+				.filter(|&&(id, _)| interface.contains(&id))
+				.map(|(_, var)| var.clone())
+				.collect();
+
+			vars.sort_by_key(|v| v.location);
+			vars
+		};
+
+		reflection.entry_points.push(EntryPoint {
+			name, stage,
+			inputs: filter(&inputs),
+			outputs: filter(&outputs),
+		});
+	}
+
+	Ok(reflection)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const IMAGE_SAMPLED: u32 = 1;
+	const IMAGE_STORAGE: u32 = 2;
+	const DIM_2D: u32 = 1;
+	const BLOCK: u32 = 2;
+
+	/// Pack a string into nul-terminated words.
+	fn pack(string: &str) -> Vec<u32> {
+		let mut bytes = string.as_bytes().to_vec();
+
+		bytes.push(0);
+		while bytes.len() % 4 != 0 {
+			bytes.push(0);
+		}
+
+		bytes.chunks(4).map(|b| {
+			b[0] as u32 | (b[1] as u32) << 8 | (b[2] as u32) << 16
+				| (b[3] as u32) << 24
+		}).collect()
+	}
+
+	/// Hand-assembled SPIR-V.
+	struct Asm(Vec<u32>);
+
+	impl Asm {
+		fn new() -> Self {
+			Asm(vec![MAGIC, 0x00010000, 0, 100, 0])
+		}
+
+		fn op(mut self, opcode: u32, operands: &[u32]) -> Self {
+			self.0.push((operands.len() as u32 + 1) << 16 | opcode);
+			self.0.extend_from_slice(operands);
+			self
+		}
+
+		fn name(self, id: u32, name: &str) -> Self {
+			let mut operands = vec![id];
+
+			operands.extend(pack(name));
+			self.op(OP_NAME, &operands)
+		}
+
+		fn member_name(self, id: u32, member: u32, name: &str) -> Self {
+			let mut operands = vec![id, member];
+
+			operands.extend(pack(name));
+			self.op(OP_MEMBER_NAME, &operands)
+		}
+
+		fn entry_point(self, model: u32, name: &str, interface: &[u32])
+			-> Self
+		{
+			let mut operands = vec![model, 100];
+
+			operands.extend(pack(name));
+			operands.extend_from_slice(interface);
+			self.op(OP_ENTRY_POINT, &operands)
+		}
+
+		fn decorate(self, id: u32, decoration: u32, value: u32) -> Self {
+			self.op(OP_DECORATE, &[id, decoration, value])
+		}
+
+		fn member_decorate(self, id: u32, member: u32, decoration: u32,
+			value: u32) -> Self
+		{
+			self.op(OP_MEMBER_DECORATE, &[id, member, decoration,
+				value])
+		}
+
+		/// Declare variable `id` of a new pointer type `id - 1`.
+		fn variable(self, id: u32, storage: u32, ty: u32) -> Self {
+			self.op(OP_TYPE_POINTER, &[id - 1, storage, ty])
+				.op(OP_VARIABLE, &[id - 1, id, storage])
+		}
+
+		/// Decorate variable `id` with its set and binding.
+		fn binding(self, id: u32, set: u32, binding: u32) -> Self {
+			self.decorate(id, DESCRIPTOR_SET, set)
+				.decorate(id, BINDING, binding)
+		}
+
+		fn reflect(&self) -> Result<Reflection, String> {
+			reflect(&self.0)
+		}
+	}
+
+	fn float(components: u32, columns: u32) -> Type {
+		Type { scalar: Scalar::Float, width: 32, components, columns }
+	}
+
+	fn interface(location: u32, name: &str, ty: Type, count: u32)
+		-> Interface
+	{
+		Interface { location, name: name.to_string(), ty, count }
+	}
+
+	/// Get `words` as little-endian bytes.
+	fn bytes(words: &[u32]) -> Vec<u8> {
+		words.iter().flat_map(|w| {
+			vec![*w as u8, (w >> 8) as u8, (w >> 16) as u8,
+				(w >> 24) as u8]
+		}).collect()
+	}
+
+	#[test]
+	fn words_from_bytes() {
+		let module = Asm::new().entry_point(4, "main", &[]).0;
+		let mut bytes = bytes(&module);
+
+		assert_eq!(words(&bytes), Ok(module.clone()));
+
+		// Unaligned
+		bytes.insert(0, 0);
+		assert_eq!(words(&bytes[1..]), Ok(module.clone()));
+		bytes.remove(0);
+
+		// Written on a machine with the other byte order
+		for word in bytes.chunks_mut(4) {
+			word.reverse();
+		}
+		assert_eq!(words(&bytes), Ok(module.clone()));
+		assert_eq!(entry_points(&words(&bytes).unwrap()),
+			vec![(Stages::FRAGMENT, "main".to_string())]);
+	}
+
+	#[test]
+	fn words_errors() {
+		let header = bytes(&Asm::new().0);
+
+		assert_eq!(words(&header[..19]),
+			Err("Length isn't a multiple of 4".to_string()));
+		assert_eq!(words(&header[..16]),
+			Err("Too short for a SPIR-V header".to_string()));
+		assert_eq!(words(&[]),
+			Err("Too short for a SPIR-V header".to_string()));
+		assert_eq!(words(&[0; 20]),
+			Err("Bad magic number 0x00000000".to_string()));
+
+		// Off by a byte
+		let mut shifted = vec![0];
+
+		shifted.extend_from_slice(&header[..19]);
+		assert_eq!(words(&shifted),
+			Err("Bad magic number 0x23020300".to_string()));
+	}
+
+	#[test]
+	fn descriptors() {
+		let reflection = Asm::new()
+			.op(OP_TYPE_FLOAT, &[1, 32])
+			.op(OP_TYPE_VECTOR, &[2, 1, 4])
+			.op(OP_TYPE_INT, &[3, 32, 0])
+			.op(OP_CONSTANT, &[3, 4, 3])
+			// Uniform block
+			.op(OP_TYPE_STRUCT, &[5, 2])
+			.decorate(5, BLOCK, 0)
+			.variable(7, UNIFORM, 5).name(7, "ubo").binding(7, 0, 0)
+			// Old-style storage block
+			.op(OP_TYPE_RUNTIME_ARRAY, &[8, 1])
+			.op(OP_TYPE_STRUCT, &[9, 8])
+			.decorate(9, BUFFER_BLOCK, 0)
+			.variable(11, UNIFORM, 9).name(11, "old").binding(11, 0, 1)
+			// Storage block in the StorageBuffer storage class,
+			// declared before lower sets.
+			.variable(13, STORAGE_BUFFER, 9).name(13, "ssbo")
+			.binding(13, 1, 0)
+			// Combined image sampler, and an array of them
+			.op(OP_TYPE_IMAGE, &[14, 1, DIM_2D, 0, 0, 0,
+				IMAGE_SAMPLED, 0])
+			.op(OP_TYPE_SAMPLED_IMAGE, &[15, 14])
+			.variable(17, UNIFORM_CONSTANT, 15).name(17, "texture")
+			.binding(17, 0, 2)
+			.op(OP_TYPE_ARRAY, &[18, 15, 4])
+			.variable(20, UNIFORM_CONSTANT, 18).name(20, "textures")
+			.binding(20, 0, 6)
+			// Storage image
+			.op(OP_TYPE_IMAGE, &[21, 1, DIM_2D, 0, 0, 0,
+				IMAGE_STORAGE, 1])
+			.variable(23, UNIFORM_CONSTANT, 21).name(23, "image")
+			.binding(23, 0, 3)
+			// Texel buffers
+			.op(OP_TYPE_IMAGE, &[24, 1, DIM_BUFFER, 0, 0, 0,
+				IMAGE_SAMPLED, 0])
+			.variable(26, UNIFORM_CONSTANT, 24).name(26, "texels")
+			.binding(26, 0, 4)
+			.op(OP_TYPE_IMAGE, &[27, 1, DIM_BUFFER, 0, 0, 0,
+				IMAGE_STORAGE, 1])
+			.variable(29, UNIFORM_CONSTANT, 27).name(29, "rw_texels")
+			.binding(29, 0, 5)
+			.reflect().unwrap();
+
+		let descriptors: Vec<(u32, u32, &str, Descriptor, u32)> =
+			reflection.descriptors.iter().map(|d| {
+				(d.set, d.binding, d.name.as_str(), d.descriptor,
+					d.count)
+			}).collect();
+
+		assert_eq!(descriptors, vec![
+			(0, 0, "ubo", Descriptor::Uniform, 1),
+			(0, 1, "old", Descriptor::Storage, 1),
+			(0, 2, "texture", Descriptor::Sampler, 1),
+			(0, 3, "image", Descriptor::StorageImage, 1),
+			(0, 4, "texels", Descriptor::UniformTexel, 1),
+			(0, 5, "rw_texels", Descriptor::StorageTexel, 1),
+			(0, 6, "textures", Descriptor::Sampler, 3),
+			(1, 0, "ssbo", Descriptor::Storage, 1),
+		]);
+		assert_eq!(reflection.push_constants, 0);
+	}
+
+	#[test]
+	fn separate_sampler() {
+		let error = Asm::new()
+			.op(OP_TYPE_SAMPLER, &[1])
+			.variable(3, UNIFORM_CONSTANT, 1).name(3, "sampler")
+			.reflect().unwrap_err();
+
+		assert!(error.starts_with("`sampler`: Separate samplers"),
+			"{}", error);
+	}
+
+	#[test]
+	fn push_constants() {
+		// struct { mat4 m; float a[3]; vec2 v; }, std430 with padded
+		// array elements.
+		let reflection = Asm::new()
+			.op(OP_TYPE_FLOAT, &[1, 32])
+			.op(OP_TYPE_VECTOR, &[2, 1, 4])
+			.op(OP_TYPE_MATRIX, &[3, 2, 4])
+			.op(OP_TYPE_INT, &[4, 32, 0])
+			.op(OP_CONSTANT, &[4, 5, 3])
+			.op(OP_TYPE_ARRAY, &[6, 1, 5])
+			.decorate(6, ARRAY_STRIDE, 16)
+			.op(OP_TYPE_VECTOR, &[7, 1, 2])
+			.op(OP_TYPE_STRUCT, &[8, 3, 6, 7])
+			.member_decorate(8, 0, OFFSET, 0)
+			.member_decorate(8, 0, MATRIX_STRIDE, 16)
+			.member_decorate(8, 1, OFFSET, 64)
+			.member_decorate(8, 2, OFFSET, 112)
+			.variable(10, PUSH_CONSTANT, 8)
+			.reflect().unwrap();
+
+		assert_eq!(reflection.push_constants, 120);
+
+		// Without decorations, members and elements are packed.
+		let reflection = Asm::new()
+			.op(OP_TYPE_FLOAT, &[1, 32])
+			.op(OP_TYPE_INT, &[2, 32, 0])
+			.op(OP_CONSTANT, &[2, 3, 3])
+			.op(OP_TYPE_ARRAY, &[4, 1, 3])
+			.op(OP_TYPE_STRUCT, &[5, 1, 4])
+			.variable(7, PUSH_CONSTANT, 5)
+			.reflect().unwrap();
+
+		assert_eq!(reflection.push_constants, 16);
+	}
+
+	#[test]
+	fn locations() {
+		let reflection = Asm::new()
+			.entry_point(0, "main", &[10, 12, 14, 17, 20, 23, 25, 27])
+			.op(OP_TYPE_FLOAT, &[1, 32])
+			.op(OP_TYPE_VECTOR, &[2, 1, 3])
+			.op(OP_TYPE_VECTOR, &[3, 1, 4])
+			.op(OP_TYPE_VECTOR, &[4, 1, 2])
+			.op(OP_TYPE_MATRIX, &[5, 4, 2])
+			.op(OP_TYPE_INT, &[6, 32, 0])
+			.op(OP_CONSTANT, &[6, 7, 2])
+			.op(OP_TYPE_ARRAY, &[8, 1, 7])
+			.variable(10, OUTPUT, 2).name(10, "normal")
+			.decorate(10, LOCATION, 0)
+			.variable(12, OUTPUT, 5).name(12, "rotation")
+			.decorate(12, LOCATION, 1)
+			.variable(14, OUTPUT, 8).name(14, "weights")
+			.decorate(14, LOCATION, 3)
+			// Block with member locations
+			.op(OP_TYPE_STRUCT, &[15, 3, 1])
+			.decorate(15, BLOCK, 0)
+			.member_name(15, 0, "color").member_name(15, 1, "fog")
+			.member_decorate(15, 0, LOCATION, 6)
+			.member_decorate(15, 1, LOCATION, 5)
+			.variable(17, OUTPUT, 15).name(17, "vs_out")
+			// Anonymous block at a location
+			.op(OP_TYPE_STRUCT, &[18, 4, 3])
+			.decorate(18, BLOCK, 0)
+			.member_name(18, 0, "uv").member_name(18, 1, "tint")
+			.variable(20, OUTPUT, 18).decorate(20, LOCATION, 7)
+			// gl_PerVertex
+			.op(OP_TYPE_STRUCT, &[21, 3])
+			.decorate(21, BLOCK, 0)
+			.member_decorate(21, 0, BUILT_IN, 0)
+			.variable(23, OUTPUT, 21)
+			.variable(25, INPUT, 3).name(25, "position")
+			.decorate(25, LOCATION, 0)
+			// gl_VertexIndex
+			.variable(27, INPUT, 6).decorate(27, BUILT_IN, 42)
+			.reflect().unwrap();
+
+		assert_eq!(reflection.entry_points, vec![EntryPoint {
+			name: "main".to_string(),
+			stage: Stages::VERTEX,
+			inputs: vec![interface(0, "position", float(4, 1), 1)],
+			outputs: vec![
+				interface(0, "normal", float(3, 1), 1),
+				interface(1, "rotation", float(2, 2), 1),
+				interface(3, "weights", float(1, 1), 2),
+				interface(5, "vs_out.fog", float(1, 1), 1),
+				interface(6, "vs_out.color", float(4, 1), 1),
+				interface(7, "uv", float(2, 1), 1),
+				interface(8, "tint", float(4, 1), 1),
+			],
+		}]);
+	}
+
+	#[test]
+	fn block_without_location() {
+		let error = Asm::new()
+			.op(OP_TYPE_FLOAT, &[1, 32])
+			.op(OP_TYPE_STRUCT, &[2, 1])
+			.member_name(2, 0, "fog")
+			.variable(4, OUTPUT, 2).name(4, "vs_out")
+			.reflect().unwrap_err();
+
+		assert_eq!(error, "`vs_out`: Member 0 (`fog`) has no location");
+	}
+
+	#[test]
+	fn truncated() {
+		// An entry point without a name
+		assert!(reflect(&Asm::new().op(OP_ENTRY_POINT, &[0]).0).is_err());
+		assert!(reflect(&Asm::new().op(OP_NAME, &[]).0).is_err());
+		assert!(reflect(&Asm::new().op(OP_TYPE_STRUCT, &[]).0).is_err());
+		assert!(reflect(&[MAGIC, 0x00010000, 0, 100]).is_err());
+
+		let mut words = Asm::new().op(OP_TYPE_VECTOR, &[2, 1, 4]).0;
+
+		words.pop();
+		assert_eq!(reflect(&words).unwrap_err(), "Truncated instruction");
+	}
+
+	#[test]
+	fn mismatches() {
+		let vertex = EntryPoint {
+			name: "main".to_string(),
+			stage: Stages::VERTEX,
+			inputs: vec![],
+			outputs: vec![
+				interface(0, "normal", float(3, 1), 1),
+				interface(1, "color", float(4, 1), 1),
+				interface(2, "weights", float(1, 1), 2),
+			],
+		};
+		let fragment = EntryPoint {
+			name: "main".to_string(),
+			stage: Stages::FRAGMENT,
+			inputs: vec![
+				interface(0, "normal", float(4, 1), 1),
+				interface(1, "color", float(4, 1), 1),
+				interface(2, "weights", float(1, 1), 1),
+				interface(3, "uv", float(2, 1), 1),
+			],
+			outputs: vec![],
+		};
+
+		assert_eq!(interface_mismatches(&vertex, &fragment), vec![
+			"Fragment input `normal` (Type { scalar: Float, width: \
+			32, components: 4, columns: 1 } x1) doesn't match \
+			vertex output `normal` (Type { scalar: Float, width: \
+			32, components: 3, columns: 1 } x1) at location 0",
+			"Fragment input `weights` (Type { scalar: Float, width: \
+			32, components: 1, columns: 1 } x1) doesn't match \
+			vertex output `weights` (Type { scalar: Float, width: \
+			32, components: 1, columns: 1 } x2) at location 2",
+			"Fragment input `uv` at location 3 isn't written by the \
+			vertex shader",
+		]);
+
+		let geometry = EntryPoint {
+			stage: Stages::GEOMETRY, ..vertex.clone()
+		};
+
+		assert_eq!(interface_mismatches(&geometry, &fragment)[2],
+			"Fragment input `uv` at location 3 isn't written by the \
+			geometry shader");
+		assert!(interface_mismatches(&vertex, &vertex).is_empty());
+	}
+}
@@ -0,0 +1,385 @@
+// "asi_vulkan" - Aldaron's System Interface - Vulkan
+//
+// Copyright Jeron A. Lau 2018.
+// Distributed under the Boost Software License, Version 1.0.
+// (See accompanying file LICENSE_1_0.txt or copy at
+// https://www.boost.org/LICENSE_1_0.txt)
+
+use null;
+
+use Gpu;
+use Image;
+use types::*;
+use std::{ rc::Rc, cell::RefCell };
+
+/// An offscreen render target, which can be drawn to in place of the
+/// swapchain and then sampled as a texture.
+#[derive(Clone)] pub struct RenderTarget(Rc<RenderTargetContext>);
+
+struct RenderTargetContext {
+	#[allow(unused)] // To keep in scope, preventing segfault.
+	ms_images: Vec<Image>,
+	// Single-sampled images that the multisampled images (if any) resolve
+	// into, or that are drawn to directly.
+	images: Vec<Image>,
+	depth: Option<Image>,
+	render_pass: VkRenderPass,
+	// Compatible render passes that load some attachments instead of
+	// clearing them, created when first needed.
+	load_passes: RefCell<Vec<(Vec<bool>, VkRenderPass)>>,
+	framebuffer: VkFramebuffer,
+	extent: VkExtent2D,
+	vulkan: Gpu,
+}
+
+impl RenderTarget {
+	/// Create a new render target with `ncolors` color images, and
+	/// optionally a depth buffer.  Styles built from `render_pass()` of a
+	/// render target with 1 color image and depth may also be used on the
+	/// swapchain, and vice-versa.
+	pub fn new(vulkan: &Gpu, width: u32, height: u32, ncolors: u32,
+		depth: bool) -> Self
+	{ unsafe {
+		assert!(ncolors != 0, "RenderTarget needs a color image");
+
+		let format = vulkan.get().format.clone();
+		let samples = vulkan.get().sample_count;
+		let mut ms_images = vec![];
+		let mut images = vec![];
+
+		for _ in 0..ncolors {
+			if multisampled(samples) {
+				ms_images.push(Image::new(vulkan, width, height,
+					format.clone(), VkImageTiling::Optimal,
+					VkImageUsage::ColorAttachmentBit,
+					VkImageLayout::Undefined, 0, samples));
+			}
+			images.push(Image::new(vulkan, width, height,
+				format.clone(), VkImageTiling::Optimal,
+				VkImageUsage::ColorAttachmentSampledTransferSrc,
+				VkImageLayout::Undefined, 0, VkSampleCount::Sc1));
+		}
+
+		let depth = if depth {
+			Some(Image::new(vulkan, width, height,
+				VkFormat::D16Unorm, VkImageTiling::Optimal,
+				VkImageUsage::DepthStencilAttachmentBit,
+				VkImageLayout::Undefined, 0, samples))
+		} else {
+			None
+		};
+
+		let nclears = if depth.is_some() { ncolors + 1 } else { ncolors };
+		let render_pass = create_render_pass(vulkan, format, ncolors,
+			depth.is_some(), &vec![false; nclears as usize]);
+
+		// Attachments are ordered: multisampled, depth, resolved; or
+		// without multisampling: color, depth.
+		let mut attachments = vec![];
+		let colors = if ms_images.is_empty() { &images } else { &ms_images };
+		for image in colors.iter() {
+			attachments.push(image.view());
+		}
+		if let Some(ref depth) = depth {
+			attachments.push(depth.view());
+		}
+		if !ms_images.is_empty() {
+			for image in images.iter() {
+				attachments.push(image.view());
+			}
+		}
+
+		let mut framebuffer = VkFramebuffer(0);
+
+		(vulkan.get().create_framebuffer)(
+			vulkan.get().device,
+			&VkFramebufferCreateInfo {
+				s_type: VkStructureType::FramebufferCreateInfo,
+				p_next: null(),
+				flags: 0,
+				attachment_count: attachments.len() as u32,
+				attachments: attachments.as_ptr(),
+				layers: 1,
+				render_pass,
+				width,
+				height,
+			},
+			null(),
+			&mut framebuffer
+		).unwrap();
+
+		RenderTarget(Rc::new(RenderTargetContext {
+			ms_images, images, depth, render_pass, framebuffer,
+			load_passes: RefCell::new(vec![]),
+			extent: VkExtent2D { width, height },
+			vulkan: vulkan.clone(),
+		}))
+	} }
+
+	/// Get the render pass, for building `Style`s that draw to this target.
+	pub fn render_pass(&self) -> VkRenderPass {
+		self.0.render_pass
+	}
+
+	/// Get the framebuffer for this target.
+	pub fn framebuffer(&self) -> VkFramebuffer {
+		self.0.framebuffer
+	}
+
+	/// Get the resolved color image at `index`, which can be used as a
+	/// `Sprite` texture once rendering to this target has finished.
+	pub fn color(&self, index: usize) -> Image {
+		self.0.images[index].clone()
+	}
+
+	/// Get the number of color images.
+	pub fn ncolors(&self) -> usize {
+		self.0.images.len()
+	}
+
+	/// Get the depth image, if there is one.
+	pub fn depth(&self) -> Option<Image> {
+		self.0.depth.clone()
+	}
+
+	/// Get the width and height of this target.
+	pub fn size(&self) -> (u32, u32) {
+		(self.0.extent.width, self.0.extent.height)
+	}
+
+	pub(crate) fn extent(&self) -> VkExtent2D {
+		self.0.extent
+	}
+
+	/// Get a render pass compatible with `render_pass()` that loads the
+	/// color (then depth) attachments that are `true` in `load`.
+	pub(crate) fn render_pass_for(&self, load: &[bool]) -> VkRenderPass {
+		let nclears = self.0.images.len()
+			+ if self.0.depth.is_some() { 1 } else { 0 };
+
+		assert_eq!(load.len(), nclears, "Need a Clear for each image");
+
+		if !load.contains(&true) {
+			return self.0.render_pass;
+		}
+
+		let mut load_passes = self.0.load_passes.borrow_mut();
+
+		for &(ref which, render_pass) in load_passes.iter() {
+			if which.as_slice() == load {
+				return render_pass;
+			}
+		}
+
+		let render_pass = unsafe {
+			create_render_pass(&self.0.vulkan,
+				self.0.vulkan.get().format.clone(),
+				self.0.images.len() as u32,
+				self.0.depth.is_some(), load)
+		};
+
+		load_passes.push((load.to_vec(), render_pass));
+
+		render_pass
+	}
+}
+
+/// `load` has whether or not to load each color attachment, then depth.
+unsafe fn create_render_pass(vulkan: &Gpu, format: VkFormat, ncolors: u32,
+	depth: bool, load: &[bool]) -> VkRenderPass
+{
+	let connection = vulkan.get();
+	let mut render_pass = VkRenderPass(0);
+	let multisampled = multisampled(connection.sample_count);
+
+	let depth_index = ncolors;
+	let resolve_index = if depth { ncolors + 1 } else { ncolors };
+
+	let mut attachments = vec![];
+	let mut color_refs = vec![];
+	let mut resolve_refs = vec![];
+
+	// Itermediary, stored so that it can be loaded next time.  Without
+	// multisampling, these are the color buffers, left in the layout
+	// `Sprite` samples them with.
+	let color_layout = if multisampled {
+		VkImageLayout::ColorAttachmentOptimal
+	} else {
+		VkImageLayout::General
+	};
+	for i in 0..ncolors {
+		let (load_op, initial_layout) = load_or_clear(load[i as usize],
+			color_layout);
+
+		attachments.push(VkAttachmentDescription {
+			flags: 0,
+			format: format.clone(),
+			samples: connection.sample_count,
+			load_op,
+			store_op: VkAttachmentStoreOp::Store,
+			stencil_load_op: VkAttachmentLoadOp::DontCare,
+			stencil_store_op: VkAttachmentStoreOp::DontCare,
+			initial_layout,
+			final_layout: color_layout,
+		});
+		color_refs.push(VkAttachmentReference {
+			attachment: i,
+			layout: VkImageLayout::ColorAttachmentOptimal,
+		});
+	}
+	// Depth Buffer
+	if depth {
+		let (load_op, initial_layout) = load_or_clear(
+			load[depth_index as usize],
+			VkImageLayout::DepthStencilAttachmentOptimal);
+
+		attachments.push(VkAttachmentDescription {
+			flags: 0,
+			format: VkFormat::D16Unorm,
+			samples: connection.sample_count,
+			load_op,
+			store_op: VkAttachmentStoreOp::Store,
+			stencil_load_op: VkAttachmentLoadOp::DontCare,
+			stencil_store_op: VkAttachmentStoreOp::DontCare,
+			initial_layout,
+			final_layout:
+				VkImageLayout::DepthStencilAttachmentOptimal,
+		});
+	}
+	// Color Buffers, left in the layout `Sprite` samples them with.
+	for i in 0..(if multisampled { ncolors } else { 0 }) {
+		attachments.push(VkAttachmentDescription {
+			flags: 0,
+			format: format.clone(),
+			samples: VkSampleCount::Sc1,
+			load_op: VkAttachmentLoadOp::DontCare,
+			store_op: VkAttachmentStoreOp::Store,
+			stencil_load_op: VkAttachmentLoadOp::DontCare,
+			stencil_store_op: VkAttachmentStoreOp::DontCare,
+			initial_layout: VkImageLayout::Undefined,
+			final_layout: VkImageLayout::General,
+		});
+		resolve_refs.push(VkAttachmentReference {
+			attachment: resolve_index + i,
+			layout: VkImageLayout::ColorAttachmentOptimal,
+		});
+	}
+
+	let depth_ref = VkAttachmentReference {
+		attachment: depth_index,
+		layout: VkImageLayout::DepthStencilAttachmentOptimal,
+	};
+	let mut dependencies = vec![
+		VkSubpassDependency {
+			src_subpass: !0,
+			dst_subpass: 0,
+			src_stage_mask: VkPipelineStage::FragmentShader,
+			dst_stage_mask: VkPipelineStage::ColorAttachmentOutput,
+			src_access_mask: VkAccess::ShaderReadBit,
+			dst_access_mask: VkAccess::ColorAttachmentReadWrite,
+			dependency_flags: 0,
+		},
+		// Finish writing before sampling as a texture.
+		VkSubpassDependency {
+			src_subpass: 0,
+			dst_subpass: !0,
+			src_stage_mask: VkPipelineStage::ColorAttachmentOutput,
+			dst_stage_mask: VkPipelineStage::FragmentShader,
+			src_access_mask: VkAccess::ColorAttachmentWriteBit,
+			dst_access_mask: VkAccess::ShaderReadBit,
+			dependency_flags: 0,
+		},
+	];
+
+	if depth && load[depth_index as usize] {
+		dependencies.push(depth_load_dependency());
+	}
+
+	(connection.new_renderpass)(
+		connection.device,
+		&VkRenderPassCreateInfo {
+			s_type: VkStructureType::RenderPassCreateInfo,
+			p_next: null(),
+			flags: 0,
+			attachment_count: attachments.len() as u32,
+			attachments: attachments.as_ptr(),
+			subpass_count: 1,
+			subpasses: &VkSubpassDescription {
+				flags: 0,
+				pipeline_bind_point: VkPipelineBindPoint::Graphics,
+				color_attachment_count: ncolors,
+				color_attachments: color_refs.as_ptr(),
+				depth_stencil_attachment: if depth {
+					&depth_ref
+				} else {
+					null()
+				},
+				input_attachment_count: 0,
+				input_attachments: null(),
+				preserve_attachment_count: 0,
+				preserve_attachments: null(),
+				// Resolve sources must be multisampled.
+				resolve_attachments: if multisampled {
+					resolve_refs.as_ptr()
+				} else {
+					null()
+				},
+			},
+			dependency_count: dependencies.len() as u32,
+			dependencies: dependencies.as_ptr(),
+		},
+		null(),
+		&mut render_pass
+	).unwrap();
+
+	render_pass
+}
+
+/// Whether or not color and depth attachments with `samples` are resolved
+/// into single-sampled images.
+fn multisampled(samples: VkSampleCount) -> bool {
+	!matches!(samples, VkSampleCount::Sc1)
+}
+
+/// Get the load op and initial layout for an attachment that is left in
+/// `layout` at the end of the render pass.
+pub(crate) fn load_or_clear(load: bool, layout: VkImageLayout)
+	-> (VkAttachmentLoadOp, VkImageLayout)
+{
+	if load {
+		(VkAttachmentLoadOp::Load, layout)
+	} else {
+		(VkAttachmentLoadOp::Clear, VkImageLayout::Undefined)
+	}
+}
+
+/// Get the external dependency that makes the depth written last time
+/// visible to the depth tests of a render pass that loads it.
+pub(crate) fn depth_load_dependency() -> VkSubpassDependency {
+	VkSubpassDependency {
+		src_subpass: !0,
+		dst_subpass: 0,
+		src_stage_mask: VkPipelineStage::LateFragmentTests,
+		dst_stage_mask: VkPipelineStage::EarlyFragmentTests,
+		src_access_mask: VkAccess::DepthStencilAttachmentWriteBit,
+		dst_access_mask: VkAccess::DepthStencilAttachmentReadWrite,
+		dependency_flags: 0,
+	}
+}
+
+impl Drop for RenderTargetContext {
+	fn drop(&mut self) {
+		let vk = self.vulkan.get();
+
+		unsafe {
+			(vk.drop_framebuffer)(vk.device, self.framebuffer,
+				null());
+			(vk.drop_renderpass)(vk.device, self.render_pass,
+				null());
+			for &(_, render_pass) in self.load_passes.borrow().iter() {
+				(vk.drop_renderpass)(vk.device, render_pass,
+					null());
+			}
+		}
+	}
+}
@@ -0,0 +1,213 @@
+// "asi_vulkan" - Aldaron's System Interface - Vulkan
+//
+// Copyright Jeron A. Lau 2018.
+// Distributed under the Boost Software License, Version 1.0.
+// (See accompanying file LICENSE_1_0.txt or copy at
+// https://www.boost.org/LICENSE_1_0.txt)
+
+use types::*;
+use c_void;
+use mem;
+use std::slice;
+
+/// A value that can be given to a shader's `layout(constant_id = N)`
+/// specialization constant.
+pub trait Constant: Copy {
+	/// The value's bytes, as the shader reads them.
+	fn bytes(self) -> Vec<u8>;
+
+	/// Whether or not this is a 64-bit integer, which needs the GPU to
+	/// support `Int64` shaders.
+	fn int64() -> bool {
+		false
+	}
+
+	/// Whether or not this is a 64-bit float, which needs the GPU to
+	/// support `Float64` shaders.
+	fn float64() -> bool {
+		false
+	}
+}
+
+macro_rules! constant {
+	($($t:ty $(: $wide:ident)*),*) => {$(
+		impl Constant for $t {
+			fn bytes(self) -> Vec<u8> {
+				// In the machine's byte order.
+				unsafe {
+					slice::from_raw_parts(
+						&self as *const $t as *const u8,
+						mem::size_of::<$t>()).to_vec()
+				}
+			}
+
+			$(fn $wide() -> bool {
+				true
+			})*
+		}
+	)*}
+}
+
+constant!(u32, i32, f32, u64: int64, i64: int64, f64: float64);
+
+impl Constant for bool {
+	/// Booleans are read as a 32-bit `VkBool32`.
+	fn bytes(self) -> Vec<u8> {
+		(self as u32).bytes()
+	}
+}
+
+/// A struct of specialization constant values.  Implement it with the
+/// `specialization!` macro.
+pub trait Constants {
+	/// Get the values of all the fields, with their constant IDs.
+	fn specialization(&self) -> Specialization;
+}
+
+/// Implement `Constants` for a struct, listing each field with its
+/// `constant_id`:
+///
+/// ```ignore
+/// #[derive(Copy, Clone)] struct Variant {
+///     fog: bool,
+///     textured: bool,
+///     fog_density: f32,
+/// }
+///
+/// specialization!(Variant { fog: 0, textured: 1, fog_density: 2 });
+/// ```
+#[macro_export]
+macro_rules! specialization {
+	($constants:ty { $($field:ident: $id:expr),* $(,)* }) => {
+		impl $crate::Constants for $constants {
+			fn specialization(&self) -> $crate::Specialization {
+				$crate::Specialization::new()
+					$(.constant($id, self.$field))*
+			}
+		}
+	}
+}
+
+/// Specialization constant values for one shader stage, to make a variant
+/// of a shader without compiling another one.
+#[derive(Clone)] pub struct Specialization {
+	entries: Vec<VkSpecializationMapEntry>,
+	data: Vec<u8>,
+	int64: bool,
+	float64: bool,
+}
+
+impl Specialization {
+	/// Create a specialization that leaves all constants at their
+	/// defaults.
+	pub fn new() -> Self {
+		Specialization {
+			entries: vec![], data: vec![], int64: false, float64: false,
+		}
+	}
+
+	/// Get the specialization for the fields of `constants`.
+	pub fn of<C: Constants>(constants: &C) -> Self {
+		constants.specialization()
+	}
+
+	/// Set the constant with `layout(constant_id = id)` to `value`, which
+	/// must have the type the shader declares it with.
+	pub fn constant<T: Constant>(mut self, id: u32, value: T) -> Self {
+		let bytes = value.bytes();
+
+		assert!(self.entries.iter().all(|e| e.constant_id != id),
+			"Specialization constant {} set twice", id);
+
+		self.entries.push(VkSpecializationMapEntry {
+			constant_id: id,
+			offset: self.data.len() as u32,
+			size: bytes.len(),
+		});
+		self.data.extend(bytes);
+		self.int64 |= T::int64();
+		self.float64 |= T::float64();
+		self
+	}
+
+	/// Check that the GPU supports the types of the constants.
+	pub(crate) fn check(&self, features: &VkPhysicalDeviceFeatures) {
+		assert!(!self.int64 || features.shader_int64 != 0,
+			"GPU doesn't support 64-bit integers in shaders");
+		assert!(!self.float64 || features.shader_float64 != 0,
+			"GPU doesn't support 64-bit floats in shaders");
+	}
+
+	/// Get the specialization info, which points into `self`.
+	pub(crate) fn vk(&self) -> VkSpecializationInfo {
+		VkSpecializationInfo {
+			map_entry_count: self.entries.len() as u32,
+			map_entries: self.entries.as_ptr(),
+			data_size: self.data.len(),
+			data: self.data.as_ptr() as *const c_void,
+		}
+	}
+}
+
+impl Default for Specialization {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[derive(Copy, Clone)] struct Variant {
+		fog: bool,
+		fog_density: f32,
+		seed: u64,
+	}
+
+	specialization!(Variant { fog: 0, fog_density: 2, seed: 1 });
+
+	fn entries(specialization: &Specialization) -> Vec<(u32, u32, usize)> {
+		specialization.entries.iter()
+			.map(|e| (e.constant_id, e.offset, e.size))
+			.collect()
+	}
+
+	#[test]
+	fn entries_and_offsets() {
+		let specialization = Specialization::of(&Variant {
+			fog: true, fog_density: 0.5, seed: 7,
+		});
+
+		assert_eq!(entries(&specialization),
+			vec![(0, 0, 4), (2, 4, 4), (1, 8, 8)]);
+		assert_eq!(specialization.data.len(), 16);
+		assert_eq!(&specialization.data[4..8], &0.5f32.bytes()[..]);
+		assert_eq!(&specialization.data[8..], &7u64.bytes()[..]);
+		assert!(specialization.int64 && !specialization.float64);
+
+		let info = specialization.vk();
+
+		assert_eq!(info.map_entry_count, 3);
+		assert_eq!(info.data_size, 16);
+	}
+
+	#[test]
+	fn bool_is_32_bits() {
+		assert_eq!(true.bytes(), 1u32.bytes());
+		assert_eq!(false.bytes(), vec![0; 4]);
+
+		let specialization = Specialization::new()
+			.constant(3, true)
+			.constant(4, 2.0f64);
+
+		assert_eq!(entries(&specialization), vec![(3, 0, 4), (4, 4, 8)]);
+		assert!(!specialization.int64 && specialization.float64);
+	}
+
+	#[test]
+	#[should_panic(expected = "Specialization constant 1 set twice")]
+	fn set_twice() {
+		Specialization::new().constant(1, 1u32).constant(1, 2i32);
+	}
+}
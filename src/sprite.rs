@@ -15,6 +15,7 @@ use Style;
 use memory::{ Buffer, BufferBuilderType, Memory };
 use Gpu;
 use Image;
+use descriptor::DescriptorSetWriter;
 use std::{ rc::Rc };
 
 /// A render-able instance.
@@ -151,123 +152,6 @@ unsafe fn txuniform(vulkan: &Gpu, device: VkDevice,
 	writer.update_descriptor_sets(vulkan, device);
 }
 
-struct DescriptorSetWriter {
-	sets: [Set; 255],
-	nwrites: u8,
-}
-
-impl DescriptorSetWriter {
-	/// Create a new DescriptorSetWriter.
-	#[inline(always)]
-	pub fn new() -> Self {
-		Self {
-			sets: unsafe { mem::uninitialized() },
-			nwrites: 0,
-		}
-	}
-
-	/// Write a uniform buffer to the descriptor set.
-	#[inline(always)]
-	pub fn uniform(mut self, desc_set: VkDescriptorSet, memory: &Buffer)
-		-> Self
-	{
-		self.sets[self.nwrites as usize] = Set::Uniform(desc_set,
-			memory.buffer());
-
-		self.nwrites += 1;
-
-		self
-	}
-
-	/// Write an image sampler to the descriptor set.
-	#[inline(always)]
-	pub fn sampler(mut self, desc_set: VkDescriptorSet,
-		tex_sampler: VkSampler, tex_view: VkImageView) -> Self
-	{
-		self.sets[self.nwrites as usize] = Set::Sampler(desc_set, tex_sampler, tex_view);
-
-		self.nwrites += 1;
-
-		self
-	}
-
-	/// Update the descriptor sets.
-	#[inline(always)]
-	pub fn update_descriptor_sets(&self, connection: &Gpu,
-		device: VkDevice) -> ()
-	{
-		let connection = connection.get();
-
-		let mut buffer_infos: [VkDescriptorBufferInfo; 255] = unsafe {
-			mem::uninitialized()
-		};
-		let mut image_infos: [VkDescriptorImageInfo; 255] = unsafe {
-			mem::uninitialized()
-		};
-		let mut writes: [VkWriteDescriptorSet; 255] = unsafe {
-			mem::uninitialized()
-		};
-
-		for i in 0..self.nwrites {
-			match self.sets[i as usize] {
-				Set::Sampler(desc_set, tex_sampler, tex_view) => {
-					image_infos[i as usize] = VkDescriptorImageInfo {
-						sampler: tex_sampler,
-						image_view: tex_view,
-						image_layout: VkImageLayout::General,
-					};
-					writes[i as usize] = VkWriteDescriptorSet {
-						s_type: VkStructureType::WriteDescriptorSet,
-						next: null(),
-						dst_set: desc_set,
-						dst_binding: i as u32,
-						descriptor_count: 1, //tex_count,
-						descriptor_type: VkDescriptorType::CombinedImageSampler,
-						image_info: &image_infos[i as usize],
-						buffer_info: null(),
-						dst_array_element: 0,
-						texel_buffer_view: null(),
-					};
-				}
-				Set::Uniform(desc_set, buffer) => {				
-					buffer_infos[i as usize] = VkDescriptorBufferInfo {
-						buffer: buffer,
-						offset: 0,
-						range: !0,
-					};
-					writes[i as usize] = VkWriteDescriptorSet {
-						s_type: VkStructureType::WriteDescriptorSet,
-						next: null(),
-						dst_set: desc_set,
-						dst_binding: i as u32,
-						descriptor_count: 1,
-						descriptor_type: VkDescriptorType::UniformBuffer,
-						buffer_info: &buffer_infos[i as usize],
-						dst_array_element: 0,
-						texel_buffer_view: null(),
-						image_info: null(),
-					};
-				}
-			}
-		}
-
-		unsafe {
-			(connection.update_descsets)(
-				device,
-				self.nwrites as u32,
-				writes.as_ptr(),
-				0,
-				null(),
-			);
-		}
-	}
-}
-
-enum Set {
-	Uniform(VkDescriptorSet, VkBuffer),
-	Sampler(VkDescriptorSet, VkSampler, VkImageView),
-}
-
 impl Drop for SpriteContext {
 	fn drop(&mut self) {
 		let vk = self.vulkan.get();
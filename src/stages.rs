@@ -0,0 +1,64 @@
+// "asi_vulkan" - Aldaron's System Interface - Vulkan
+//
+// Copyright Jeron A. Lau 2018.
+// Distributed under the Boost Software License, Version 1.0.
+// (See accompanying file LICENSE_1_0.txt or copy at
+// https://www.boost.org/LICENSE_1_0.txt)
+
+use types::VkShaderStage;
+use std::ops::BitOr;
+
+/// A set of shader stages, combined with `|`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)] pub struct Stages(pub(crate) u32);
+
+impl Stages {
+	pub const VERTEX: Stages = Stages(0x00000001);
+	pub const TESSELLATION_CONTROL: Stages = Stages(0x00000002);
+	pub const TESSELLATION_EVALUATION: Stages = Stages(0x00000004);
+	pub const GEOMETRY: Stages = Stages(0x00000008);
+	pub const FRAGMENT: Stages = Stages(0x00000010);
+	pub const COMPUTE: Stages = Stages(0x00000020);
+	pub const ALL_GRAPHICS: Stages = Stages(0x0000001f);
+
+	/// Whether or not all stages in `other` are in this set.
+	pub fn contains(&self, other: Stages) -> bool {
+		self.0 & other.0 == other.0
+	}
+
+	/// Get the Vulkan stage of a single shader stage.
+	pub(crate) fn vk(&self) -> VkShaderStage {
+		match *self {
+			Stages::VERTEX => VkShaderStage::Vertex,
+			Stages::TESSELLATION_CONTROL =>
+				VkShaderStage::TessellationControl,
+			Stages::TESSELLATION_EVALUATION =>
+				VkShaderStage::TessellationEvaluation,
+			Stages::GEOMETRY => VkShaderStage::Geometry,
+			Stages::FRAGMENT => VkShaderStage::Fragment,
+			Stages::COMPUTE => VkShaderStage::Compute,
+			_ => panic!("Not a single shader stage: {:?}", self),
+		}
+	}
+
+	/// Get the name of a single shader stage, for messages.
+	pub(crate) fn name(&self) -> &'static str {
+		match *self {
+			Stages::VERTEX => "vertex",
+			Stages::TESSELLATION_CONTROL => "tessellation control",
+			Stages::TESSELLATION_EVALUATION =>
+				"tessellation evaluation",
+			Stages::GEOMETRY => "geometry",
+			Stages::FRAGMENT => "fragment",
+			Stages::COMPUTE => "compute",
+			_ => panic!("Not a single shader stage: {:?}", self),
+		}
+	}
+}
+
+impl BitOr for Stages {
+	type Output = Stages;
+
+	fn bitor(self, other: Stages) -> Stages {
+		Stages(self.0 | other.0)
+	}
+}
@@ -9,10 +9,17 @@ use null;
 use mem;
 
 use Gpu;
-use VK_SAMPLE_COUNT;
 use ShaderModule;
+use PipelineCache;
+use Specialization;
+use Stages;
+use raster::*;
+use blend::*;
+use vertex::*;
+use descriptor::{ self, Descriptor };
+use interface_mismatches;
 use types::*;
-use std::{ rc::Rc };
+use std::{ rc::Rc, collections::BTreeMap };
 
 pub struct Style(Rc<StyleContext>);
 
@@ -20,16 +27,571 @@ struct StyleContext {
 	pipeline: u64,
 	pipeline_layout: u64,
 	descsetlayout: u64,
+	instance_binding: Option<u32>,
+	descriptors0: Option<Vec<Descriptor>>,
+	descriptors: Vec<Descriptor>,
+	descsetlayout1: Option<VkDescriptorSetLayout>,
 	vulkan: Gpu,
 }
 
+/// Builds a `Style` with pipeline state other than the defaults of
+/// `Style::new()`.
+pub struct StyleBuilder<'a> {
+	render_pass: VkRenderPass,
+	vertex: &'a ShaderModule,
+	fragment: &'a ShaderModule,
+	geometry: Option<&'a ShaderModule>,
+	tessellation: Option<(&'a ShaderModule, &'a ShaderModule)>,
+	ntextures: u32,
+	layout: VertexLayout,
+	attachments: Vec<Attachment>,
+	logic_op: Option<LogicOp>,
+	blend_constants: [f32; 4],
+	dynamic_blend_constants: bool,
+	nviewports: u32,
+	primitive_restart: bool,
+	ninstance_attributes: u32,
+	push_constants: Vec<(Stages, u32, u32)>,
+	descriptors0: Option<Vec<Descriptor>>,
+	descriptors: Vec<Descriptor>,
+	specializations: Vec<(Stages, Specialization)>,
+	cache: Option<&'a PipelineCache>,
+	topology: Topology,
+	cull_mode: CullMode,
+	front_face: FrontFace,
+	polygon_mode: PolygonMode,
+	line_width: f32,
+	depth_test: bool,
+	depth_write: bool,
+	depth_compare: CompareOp,
+	depth_bias: Option<DepthBias>,
+	depth_clamp: bool,
+}
+
+impl<'a> StyleBuilder<'a> {
+	/// Start building a `Style` that draws opaque, filled, back-face
+	/// culled (counter-clockwise front faces) triangle fans to one
+	/// viewport, depth tested and written with `LessOrEqual`.
+	pub fn new(render_pass: VkRenderPass, vertex: &'a ShaderModule,
+		fragment: &'a ShaderModule, ntextures: u32, nvbuffers: u32)
+		-> Self
+	{
+		StyleBuilder {
+			render_pass, vertex, fragment, ntextures,
+			geometry: None,
+			tessellation: None,
+			layout: VertexLayout::vec4s(nvbuffers),
+			attachments: vec![Attachment::new(None)],
+			logic_op: None,
+			blend_constants: [0.0; 4],
+			dynamic_blend_constants: false,
+			nviewports: 1,
+			primitive_restart: false,
+			ninstance_attributes: 0,
+			push_constants: vec![],
+			descriptors0: None,
+			descriptors: vec![],
+			specializations: vec![],
+			cache: None,
+			topology: Topology::TriangleFan,
+			cull_mode: CullMode::Back,
+			front_face: FrontFace::CounterClockwise,
+			polygon_mode: PolygonMode::Fill,
+			line_width: 1.0,
+			depth_test: true,
+			depth_write: true,
+			depth_compare: CompareOp::LessOrEqual,
+			depth_bias: None,
+			depth_clamp: false,
+		}
+	}
+
+	/// Blend with what's already drawn, using alpha (`Blend::ALPHA`).
+	pub fn alpha(mut self, alpha: bool) -> Self {
+		self.attachments = vec![Attachment::new(if alpha {
+			Some(Blend::ALPHA)
+		} else {
+			None
+		})];
+		self
+	}
+
+	/// Blend with what's already drawn, using `blend` (for example,
+	/// `Blend::PREMULTIPLIED`).
+	pub fn blend(mut self, blend: Blend) -> Self {
+		self.attachments = vec![Attachment::new(Some(blend))];
+		self
+	}
+
+	/// Set the blending of each color attachment of the subpass, for
+	/// multiple render targets.  Different states for different
+	/// attachments need the GPU to support independent blending.
+	pub fn attachments(mut self, attachments: &[Attachment]) -> Self {
+		self.attachments = attachments.to_vec();
+		self
+	}
+
+	/// Replace blending with a bitwise `logic_op` (needs the GPU to support
+	/// logic ops), or `None` to blend.
+	pub fn logic_op(mut self, logic_op: Option<LogicOp>) -> Self {
+		self.logic_op = logic_op;
+		self
+	}
+
+	/// Set the RGBA color used by the `Constant*` blend factors.
+	pub fn blend_constants(mut self, blend_constants: [f32; 4]) -> Self {
+		self.blend_constants = blend_constants;
+		self
+	}
+
+	/// Set the blend constants with `cmd_set_blend_constants()` while
+	/// drawing instead of when building.
+	pub fn dynamic_blend_constants(mut self, dynamic: bool) -> Self {
+		self.dynamic_blend_constants = dynamic;
+		self
+	}
+
+	/// Use `nviewports` viewports (and scissors), up to
+	/// `Gpu::max_viewports()`.  The shaders pick which with
+	/// `gl_ViewportIndex`.
+	pub fn viewports(mut self, nviewports: u32) -> Self {
+		self.nviewports = nviewports;
+		self
+	}
+
+	/// Start a new strip or fan at every index of all ones (0xFFFF or
+	/// 0xFFFFFFFF) in indexed draws.  Not allowed with list topologies.
+	pub fn primitive_restart(mut self, primitive_restart: bool) -> Self {
+		self.primitive_restart = primitive_restart;
+		self
+	}
+
+	/// Run `geometry` on each primitive after the vertex (or tessellation)
+	/// shader.  Needs the GPU to support geometry shaders.
+	pub fn geometry(mut self, geometry: &'a ShaderModule) -> Self {
+		self.geometry = Some(geometry);
+		self
+	}
+
+	/// Tessellate patches with the `control` and `evaluation` shaders,
+	/// between the vertex and geometry (or fragment) shaders.  Needs the
+	/// GPU to support tessellation shaders, and a `Topology::Patches`
+	/// topology.
+	pub fn tessellation(mut self, control: &'a ShaderModule,
+		evaluation: &'a ShaderModule) -> Self
+	{
+		self.tessellation = Some((control, evaluation));
+		self
+	}
+
+	/// Get the shaders of each stage, in pipeline order.
+	fn shaders(&self) -> Vec<(&'a ShaderModule, Stages)> {
+		let mut shaders = vec![(self.vertex, Stages::VERTEX)];
+
+		if let Some((control, evaluation)) = self.tessellation {
+			shaders.push((control, Stages::TESSELLATION_CONTROL));
+			shaders.push((evaluation, Stages::TESSELLATION_EVALUATION));
+		}
+		if let Some(geometry) = self.geometry {
+			shaders.push((geometry, Stages::GEOMETRY));
+		}
+
+		shaders.push((self.fragment, Stages::FRAGMENT));
+		shaders
+	}
+
+	/// Read vertex buffers as described by `layout`, instead of one vec4
+	/// per buffer for `nvbuffers` buffers.
+	pub fn vertex_layout(mut self, layout: VertexLayout) -> Self {
+		self.layout = layout;
+		self
+	}
+
+	/// Read `nattributes` vec4s per instance (for example, 4 for a
+	/// transform matrix) from an instance buffer, at the locations after
+	/// the vertex buffers.  Bind it with `cmd_bind_instances()`.
+	pub fn instances(mut self, nattributes: u32) -> Self {
+		self.ninstance_attributes = nattributes;
+		self
+	}
+
+	/// Get the vertex layout with the instance buffer (if any) added, and
+	/// the instance buffer's binding.
+	fn full_layout(&self) -> (VertexLayout, Option<u32>) {
+		if self.ninstance_attributes == 0 {
+			return (self.layout.clone(), None);
+		}
+
+		let binding = self.layout.nbindings();
+		let attributes: Vec<(VertexFormat, u32)>
+			= (0..self.ninstance_attributes)
+			.map(|i| (VertexFormat::Vec4, i * 16)).collect();
+		let layout = self.layout.clone().binding(
+			16 * self.ninstance_attributes, InputRate::Instance,
+			&attributes);
+
+		(layout, Some(binding))
+	}
+
+	/// Declare `size` bytes of push constants starting `offset` bytes in,
+	/// used by `stages`.  Set them with `cmd_push_constants()`.
+	pub fn push_constants(mut self, stages: Stages, offset: u32, size: u32)
+		-> Self
+	{
+		assert!(offset.is_multiple_of(4) && size.is_multiple_of(4)
+			&& size != 0,
+			"Push constants must be whole, non-zero u32s");
+
+		self.push_constants.push((stages, offset, size));
+		self
+	}
+
+	/// Add descriptor set 1, with `descriptors` (bindings numbered in
+	/// order) used by the shaders, for example, a storage buffer of
+	/// skinning matrices.  Create it with `DescriptorSet::for_style()`.
+	pub fn descriptors(mut self, descriptors: &[Descriptor]) -> Self {
+		self.descriptors = descriptors.to_vec();
+		self
+	}
+
+	/// Start building a `Style` with descriptor sets, push constants and
+	/// vertex buffers (one per vertex input location) found by reflecting
+	/// the shaders.  Descriptor set 0 replaces the one `Style::new()` makes
+	/// (so the style can't be used with `Sprite`s), create it and set 1
+	/// with `DescriptorSet::for_style_set()`.  With geometry or
+	/// tessellation shaders, set them on `StyleBuilder::new()` and call
+	/// `reflect_stages()` instead.
+	pub fn reflect(render_pass: VkRenderPass, vertex: &'a ShaderModule,
+		fragment: &'a ShaderModule) -> Result<Self, String>
+	{
+		StyleBuilder::new(render_pass, vertex, fragment, 0, 0)
+			.reflect_stages()
+	}
+
+	/// Replace the descriptor sets, push constants and vertex buffers with
+	/// ones found by reflecting the shaders of every stage set so far (see
+	/// `reflect()`).
+	pub fn reflect_stages(mut self) -> Result<Self, String> {
+		let shaders = self.shaders();
+		let mut reflections = vec![];
+		let mut entry_points = vec![];
+
+		for &(shader, stage) in shaders.iter() {
+			let reflection = shader.reflection().map_err(|e| {
+				format!("Bad {} shader: {}", stage.name(), e)
+			})?;
+			let entry_point = reflection.entry_point(stage)
+				.ok_or_else(|| {
+					format!("No {} shader entry point",
+						stage.name())
+				})?;
+
+			reflections.push((reflection, stage));
+			entry_points.push(entry_point);
+		}
+
+		// The fragment shader reads what the last stage before it wrote.
+		let n = entry_points.len();
+		let mismatches = interface_mismatches(entry_points[n - 2],
+			entry_points[n - 1]);
+
+		if !mismatches.is_empty() {
+			return Err(mismatches.join("\n"));
+		}
+
+		// One vertex buffer per location.
+		let mut formats = vec![];
+
+		for input in entry_points[0].inputs.iter() {
+			if input.location != formats.len() as u32 {
+				return Err(format!("Vertex input `{}` should be at \
+					location {}", input.name, formats.len()));
+			}
+
+			let format = input.ty.vertex_format().ok_or_else(|| {
+				format!("Vertex input `{}` can't be read from a \
+					vertex buffer", input.name)
+			})?;
+
+			for _ in 0..input.nlocations() {
+				formats.push(format);
+			}
+		}
+
+		// Merge the descriptors of every shader.
+		let mut bindings = BTreeMap::new();
+
+		let descriptors = reflections.iter()
+			.flat_map(|&(r, _)| r.descriptors.iter());
+
+		for d in descriptors {
+			if d.set > 1 {
+				return Err(format!("`{}`: only descriptor sets 0 \
+					and 1 are supported", d.name));
+			}
+			if d.count != 1 {
+				return Err(format!("`{}`: descriptor arrays aren't \
+					supported", d.name));
+			}
+			if *bindings.entry((d.set, d.binding))
+				.or_insert(d.descriptor) != d.descriptor
+			{
+				return Err(format!("`{}`: set {} binding {} has a \
+					different type in each shader", d.name,
+					d.set, d.binding));
+			}
+		}
+
+		let mut sets = [vec![], vec![]];
+
+		for (&(set, binding), descriptor) in bindings.iter() {
+			let set = &mut sets[set as usize];
+
+			if binding != set.len() as u32 {
+				return Err(format!("Descriptor bindings must be \
+					numbered from 0 without gaps, missing {}",
+					set.len()));
+			}
+
+			set.push(*descriptor);
+		}
+
+		self.layout = VertexLayout::new().split(&formats);
+		self.descriptors0 = Some(sets[0].clone());
+		self.descriptors = sets[1].clone();
+		self.push_constants = vec![];
+
+		// One range shared by every stage that has push constants.
+		let mut size = 0;
+		let mut stages = Stages(0);
+
+		for &(reflection, stage) in reflections.iter() {
+			if reflection.push_constants != 0 {
+				size = size.max(reflection.push_constants);
+				stages = stages | stage;
+			}
+		}
+
+		if size != 0 {
+			self = self.push_constants(stages, 0, size);
+		}
+
+		Ok(self)
+	}
+
+	/// Set the specialization constants of the `stage` shader (for
+	/// example, from a struct with `Specialization::of()`), to pick a
+	/// variant of an uber-shader.  `stage` must be one stage, which the
+	/// style has a shader for when built.
+	pub fn specialize(mut self, stage: Stages, specialization: Specialization)
+		-> Self
+	{
+		assert!(stage.0.count_ones() == 1,
+			"Specialize one stage at a time, not {:?}", stage);
+
+		self.specializations.retain(|&(s, _)| s != stage);
+		self.specializations.push((stage, specialization));
+		self
+	}
+
+	/// Get the specialization info for `stage`, if any.
+	fn specialization(&self, stage: Stages) -> Option<VkSpecializationInfo> {
+		self.specializations.iter().find(|&&(s, _)| s == stage)
+			.map(|&(_, ref specialization)| specialization.vk())
+	}
+
+	/// Reuse (and add to) compiled pipelines in `cache`.
+	pub fn cache(mut self, cache: &'a PipelineCache) -> Self {
+		self.cache = Some(cache);
+		self
+	}
+
+	/// Assemble vertices into primitives with `topology`.
+	pub fn topology(mut self, topology: Topology) -> Self {
+		self.topology = topology;
+		self
+	}
+
+	/// Don't draw triangles facing `cull_mode`.
+	pub fn cull_mode(mut self, cull_mode: CullMode) -> Self {
+		self.cull_mode = cull_mode;
+		self
+	}
+
+	/// Set which winding order faces the front.
+	pub fn front_face(mut self, front_face: FrontFace) -> Self {
+		self.front_face = front_face;
+		self
+	}
+
+	/// Fill in triangles with `polygon_mode`.  Modes other than `Fill`
+	/// need the GPU to support non-solid fill modes.
+	pub fn polygon_mode(mut self, polygon_mode: PolygonMode) -> Self {
+		self.polygon_mode = polygon_mode;
+		self
+	}
+
+	/// Set the width of lines in pixels.  Widths other than 1.0 need the
+	/// GPU to support wide lines.
+	pub fn line_width(mut self, line_width: f32) -> Self {
+		self.line_width = line_width;
+		self
+	}
+
+	/// Test fragments against the depth buffer with `compare` (if `test`),
+	/// and write their depth to it (if `write`).  For example, a UI layer
+	/// drawn on top of everything would use `depth(false, false, _)`.
+	pub fn depth(mut self, test: bool, write: bool, compare: CompareOp)
+		-> Self
+	{
+		self.depth_test = test;
+		self.depth_write = write;
+		self.depth_compare = compare;
+		self
+	}
+
+	/// Offset the depth of each fragment by `depth_bias`.
+	pub fn depth_bias(mut self, depth_bias: DepthBias) -> Self {
+		self.depth_bias = Some(depth_bias);
+		self
+	}
+
+	/// Clamp depth to the viewport's depth range instead of clipping.
+	/// Needs the GPU to support depth clamping.
+	pub fn depth_clamp(mut self, depth_clamp: bool) -> Self {
+		self.depth_clamp = depth_clamp;
+		self
+	}
+
+	/// Create the `Style`.
+	pub fn build(self, connection: &mut Gpu) -> Style {
+		let max = connection.max_viewports();
+
+		assert!(self.nviewports != 0 && self.nviewports <= max,
+			"GPU supports only {} viewport(s)", max);
+		assert!(!(self.primitive_restart && self.topology.list()),
+			"Primitive restart needs a strip or fan topology");
+
+		{
+			let features = &connection.get().features;
+
+			assert!(self.polygon_mode == PolygonMode::Fill
+				|| features.fill_mode_non_solid != 0,
+				"GPU doesn't support non-solid fill modes");
+			assert!(self.line_width == 1.0
+				|| features.wide_lines != 0,
+				"GPU doesn't support wide lines");
+			let bias_clamp = self.depth_bias.map(|b| b.clamp)
+				.unwrap_or(0.0);
+
+			assert!(bias_clamp == 0.0 || features.depth_bias_clamp != 0,
+				"GPU doesn't support depth bias clamping");
+			assert!(!self.depth_clamp || features.depth_clamp != 0,
+				"GPU doesn't support depth clamping");
+			assert!(!self.attachments.is_empty(),
+				"Need at least one color attachment");
+			assert!(self.attachments.iter()
+				.all(|a| *a == self.attachments[0])
+				|| features.independent_blend != 0,
+				"GPU doesn't support independent blending");
+			assert!(self.attachments.iter().all(|a| {
+				!a.blend.map(|b| b.dual_source()).unwrap_or(false)
+			}) || features.dual_src_blend != 0,
+				"GPU doesn't support dual-source blending");
+			assert!(self.logic_op.is_none() || features.logic_op != 0,
+				"GPU doesn't support logic ops");
+			assert!(self.geometry.is_none()
+				|| features.geometry_shader != 0,
+				"GPU doesn't support geometry shaders");
+			assert!(self.tessellation.is_none()
+				|| features.tessellation_shader != 0,
+				"GPU doesn't support tessellation shaders");
+
+			let shaders = self.shaders();
+
+			for &(stage, ref specialization) in
+				self.specializations.iter()
+			{
+				assert!(shaders.iter().any(|&(_, s)| s == stage),
+					"Specialized {:?} shader isn't used", stage);
+				specialization.check(features);
+			}
+		}
+
+		{
+			let limits = &connection.get().properties.limits;
+
+			assert!(self.line_width >= limits.line_width_range[0]
+				&& self.line_width <= limits.line_width_range[1],
+				"Line width out of range {:?}",
+				limits.line_width_range);
+
+			if let Topology::Patches(n) = self.topology {
+				assert!(self.tessellation.is_some(),
+					"Patches need tessellation shaders");
+				assert!(n != 0 && n <= limits.max_tessellation_patch_size,
+					"GPU supports patches of 1 to {} control points",
+					limits.max_tessellation_patch_size);
+			} else {
+				assert!(self.tessellation.is_none(),
+					"Tessellation needs a Patches topology");
+			}
+
+			let (layout, _) = self.full_layout();
+
+			assert!(layout.nbindings()
+				<= limits.max_vertex_input_bindings,
+				"Too many vertex buffers");
+			assert!(layout.nattributes()
+				<= limits.max_vertex_input_attributes,
+				"Too many vertex attributes");
+			assert!(layout.max_stride()
+				<= limits.max_vertex_input_binding_stride,
+				"Vertex stride too large");
+			assert!(layout.max_offset()
+				<= limits.max_vertex_input_attribute_offset,
+				"Vertex attribute offset too large");
+			assert!(self.push_constants.iter().all(|&(_, o, s)| {
+				o.checked_add(s).is_some_and(|end| {
+					end <= limits.max_push_constants_size
+				})
+			}), "GPU supports only {} bytes of push constants",
+				limits.max_push_constants_size);
+		}
+
+		new_pipeline(connection, &self)
+	}
+}
+
 impl Style {
 	pub fn new(connection: &mut Gpu, render_pass: VkRenderPass,
 		vertex: &ShaderModule, fragment: &ShaderModule, ntextures: u32,
 		nvbuffers: u32, alpha: bool) -> Self
 	{
-		new_pipeline(connection, render_pass, vertex, fragment,
-			ntextures, nvbuffers, alpha)
+		StyleBuilder::new(render_pass, vertex, fragment, ntextures,
+			nvbuffers).alpha(alpha).build(connection)
+	}
+
+	/// Get the vertex buffer binding that the instance buffer is read
+	/// from, if this style is instanced.
+	pub fn instance_binding(&self) -> Option<u32> {
+		self.0 .instance_binding
+	}
+
+	/// The descriptors and layout of descriptor set 0 (if reflected) or 1
+	/// (if any).
+	pub(crate) fn descriptors(&self, set: u32)
+		-> Option<(&[Descriptor], VkDescriptorSetLayout)>
+	{
+		match set {
+			0 => self.0 .descriptors0.as_ref().map(|descriptors| {
+				(&descriptors[..], self.0 .descsetlayout)
+			}),
+			1 => self.0 .descsetlayout1.map(|layout| {
+				(&self.0 .descriptors[..], layout)
+			}),
+			_ => None,
+		}
 	}
 
 	pub (crate) fn style(&self) -> (u64, u64, u64) {
@@ -37,11 +599,61 @@ impl Style {
 	}
 }
 
-pub fn new_pipeline(vulkan: &mut Gpu, render_pass: VkRenderPass,
-	vertex: &ShaderModule, fragment: &ShaderModule, ntextures: u32,
-	nvbuffers: u32, alpha: bool) -> Style
-{ unsafe {
+fn new_pipeline(vulkan: &mut Gpu, builder: &StyleBuilder) -> Style { unsafe {
 	let connection = vulkan.get();
+	let StyleBuilder {
+		render_pass, ntextures, ref attachments,
+		logic_op, blend_constants, dynamic_blend_constants, nviewports,
+		primitive_restart, ref push_constants, ref descriptors0,
+		ref descriptors, cache, topology, cull_mode, front_face,
+		polygon_mode, line_width, depth_test, depth_write, depth_compare,
+		depth_bias, depth_clamp, ..
+	} = *builder;
+	let shaders = builder.shaders();
+	let constants: Vec<Option<VkSpecializationInfo>> = shaders.iter()
+		.map(|&(_, stage)| builder.specialization(stage)).collect();
+	let stages: Vec<VkPipelineShaderStageCreateInfo> = shaders.iter()
+		.zip(constants.iter())
+		.map(|(&(shader, stage), constants)| {
+			shader.stage_info(stage, constants.as_ref())
+		}).collect();
+	// Descriptor sets 0 and 1 are visible to both stages, or to all stages
+	// with geometry or tessellation shaders (which may also read the
+	// default set 0's vertex uniforms).
+	let (set_stages, vertex_stages) = if shaders.len() == 2 {
+		(VkShaderStage::VertexAndFragment, VkShaderStage::Vertex)
+	} else {
+		(VkShaderStage::AllGraphics, VkShaderStage::AllGraphics)
+	};
+	let tessellation_state = VkPipelineTessellationStateCreateInfo {
+		s_type: VkStructureType::PipelineTessellationStateCreateInfo,
+		next: null(),
+		flags: 0,
+		patch_control_points: match topology {
+			Topology::Patches(n) => n,
+			_ => 0,
+		},
+	};
+	let depth_bias_enable = depth_bias.is_some();
+	let depth_bias = depth_bias.unwrap_or(DepthBias::new(0.0, 0.0));
+	let attachments: Vec<VkPipelineColorBlendAttachmentState>
+		= attachments.iter().map(|a| a.vk()).collect();
+	let mut dynamic_states = vec![
+		VkDynamicState::Viewport, VkDynamicState::Scissor
+	];
+
+	if dynamic_blend_constants {
+		dynamic_states.push(VkDynamicState::BlendConstants);
+	}
+
+	let push_constants: Vec<VkPushConstantRange> = push_constants.iter()
+		.map(|&(stages, offset, size)| VkPushConstantRange {
+			stage_flags: stages.0, offset, size,
+		}).collect();
+
+	// The vertex buffers, then the instance buffer.
+	let (layout, instance_binding) = builder.full_layout();
+	let (bindings, attributes) = layout.vk();
 
 	let mut pipeline = mem::uninitialized();
 	let mut pipeline_layout = mem::uninitialized();
@@ -58,70 +670,83 @@ pub fn new_pipeline(vulkan: &mut Gpu, render_pass: VkRenderPass,
 		reference: 0,
 	};
 
-	(connection.new_descset_layout)(
-		connection.device,
-		&VkDescriptorSetLayoutCreateInfo {
-			s_type: VkStructureType::DescriptorSetLayoutCreateInfo,
-			next: null(),
-			flags: 0,
-			binding_count: 3 + ntextures,
-			// TODO: consolidate
-			bindings: if ntextures == 0 {
-				[VkDescriptorSetLayoutBinding {
-					binding: 0,
-					descriptor_type: VkDescriptorType::UniformBuffer,
-					descriptor_count: 1,
-					stage_flags: VkShaderStage::VertexAndFragment,
-					immutable_samplers: null(),
-				},
-				VkDescriptorSetLayoutBinding {
-					binding: 1,
-					descriptor_type: VkDescriptorType::UniformBuffer,
-					descriptor_count: 1,
-					stage_flags: VkShaderStage::Vertex,
-					immutable_samplers: null(),
-				},
-				VkDescriptorSetLayoutBinding {
-					binding: 2,
-					descriptor_type: VkDescriptorType::UniformBuffer,
-					descriptor_count: 1,
-					stage_flags: VkShaderStage::Fragment,
-					immutable_samplers: null(),
-				}].as_ptr()
-			} else {
-				[VkDescriptorSetLayoutBinding {
-					binding: 0,
-					descriptor_type: VkDescriptorType::UniformBuffer,
-					descriptor_count: 1,
-					stage_flags: VkShaderStage::VertexAndFragment,
-					immutable_samplers: null(),
-				},
-				VkDescriptorSetLayoutBinding {
-					binding: 1,
-					descriptor_type: VkDescriptorType::UniformBuffer,
-					descriptor_count: 1,
-					stage_flags: VkShaderStage::Vertex,
-					immutable_samplers: null(),
-				},
-				VkDescriptorSetLayoutBinding {
-					binding: 2,
-					descriptor_type: VkDescriptorType::UniformBuffer,
-					descriptor_count: 1,
-					stage_flags: VkShaderStage::Fragment,
-					immutable_samplers: null(),
+	if let Some(ref descriptors0) = *descriptors0 {
+		descsetlayout = descriptor::new_layout(vulkan, descriptors0,
+			set_stages);
+	} else {
+		(connection.new_descset_layout)(
+			connection.device,
+			&VkDescriptorSetLayoutCreateInfo {
+				s_type: VkStructureType::DescriptorSetLayoutCreateInfo,
+				next: null(),
+				flags: 0,
+				binding_count: 3 + ntextures,
+				// TODO: consolidate
+				bindings: if ntextures == 0 {
+					[VkDescriptorSetLayoutBinding {
+						binding: 0,
+						descriptor_type: VkDescriptorType::UniformBuffer,
+						descriptor_count: 1,
+						stage_flags: set_stages,
+						immutable_samplers: null(),
+					},
+					VkDescriptorSetLayoutBinding {
+						binding: 1,
+						descriptor_type: VkDescriptorType::UniformBuffer,
+						descriptor_count: 1,
+						stage_flags: vertex_stages,
+						immutable_samplers: null(),
+					},
+					VkDescriptorSetLayoutBinding {
+						binding: 2,
+						descriptor_type: VkDescriptorType::UniformBuffer,
+						descriptor_count: 1,
+						stage_flags: VkShaderStage::Fragment,
+						immutable_samplers: null(),
+					}].as_ptr()
+				} else {
+					[VkDescriptorSetLayoutBinding {
+						binding: 0,
+						descriptor_type: VkDescriptorType::UniformBuffer,
+						descriptor_count: 1,
+						stage_flags: set_stages,
+						immutable_samplers: null(),
+					},
+					VkDescriptorSetLayoutBinding {
+						binding: 1,
+						descriptor_type: VkDescriptorType::UniformBuffer,
+						descriptor_count: 1,
+						stage_flags: vertex_stages,
+						immutable_samplers: null(),
+					},
+					VkDescriptorSetLayoutBinding {
+						binding: 2,
+						descriptor_type: VkDescriptorType::UniformBuffer,
+						descriptor_count: 1,
+						stage_flags: VkShaderStage::Fragment,
+						immutable_samplers: null(),
+					},
+					VkDescriptorSetLayoutBinding {
+						binding: 3,
+						descriptor_type: VkDescriptorType::CombinedImageSampler,
+						descriptor_count: 1, // Texture Count
+						stage_flags: VkShaderStage::Fragment,
+						immutable_samplers: null(),
+					}].as_ptr()
 				},
-				VkDescriptorSetLayoutBinding {
-					binding: 3,
-					descriptor_type: VkDescriptorType::CombinedImageSampler,
-					descriptor_count: 1, // Texture Count
-					stage_flags: VkShaderStage::Fragment,
-					immutable_samplers: null(),
-				}].as_ptr()
 			},
-		},
-		null(),
-		&mut descsetlayout
-	).unwrap();
+			null(),
+			&mut descsetlayout
+		).unwrap();
+	}
+
+	let descsetlayout1 = if descriptors.is_empty() {
+		None
+	} else {
+		Some(descriptor::new_layout(vulkan, descriptors, set_stages))
+	};
+	let set_layouts: Vec<VkDescriptorSetLayout> = Some(descsetlayout)
+		.into_iter().chain(descsetlayout1).collect();
 
 	// pipeline layout:
 	(connection.new_pipeline_layout)(
@@ -130,10 +755,10 @@ pub fn new_pipeline(vulkan: &mut Gpu, render_pass: VkRenderPass,
 			s_type: VkStructureType::PipelineLayoutCreateInfo,
 			next: null(),
 			flags: 0,
-			set_layout_count: 1,
-			set_layouts: [descsetlayout].as_ptr(),
-			push_constant_range_count: 0,
-			push_constant_ranges: null(),
+			set_layout_count: set_layouts.len() as u32,
+			set_layouts: set_layouts.as_ptr(),
+			push_constant_range_count: push_constants.len() as u32,
+			push_constant_ranges: push_constants.as_ptr(),
 		},
 		null(),
 		&mut pipeline_layout
@@ -142,117 +767,66 @@ pub fn new_pipeline(vulkan: &mut Gpu, render_pass: VkRenderPass,
 	// setup shader stages:
 	(connection.new_pipeline)(
 		connection.device,
-		mem::zeroed(),
+		cache.map(|c| c.cache()).unwrap_or(VkPipelineCache(0)),
 		1,
 		&VkGraphicsPipelineCreateInfo {
 			s_type: VkStructureType::GraphicsPipelineCreateInfo,
 			next: null(),
 			flags: 0,
-			stage_count: 2,
-			stages: [
-				VkPipelineShaderStageCreateInfo {
-					s_type: VkStructureType::PipelineShaderStageCreateInfo,
-					next: null(),
-					flags: 0,
-					stage: VkShaderStage::Vertex,
-					module: vertex.0,
-					name: b"main\0".as_ptr(), // shader main function name
-					specialization_info: null(),
-				},
-				VkPipelineShaderStageCreateInfo {
-					s_type: VkStructureType::PipelineShaderStageCreateInfo,
-					next: null(),
-					flags: 0,
-					stage: VkShaderStage::Fragment,
-					module: fragment.0,
-					name: b"main\0".as_ptr(), // shader main function name
-					specialization_info: null(),
-				},
-			].as_ptr(),
+			stage_count: stages.len() as u32,
+			stages: stages.as_ptr(),
 			vertex_input_state: &VkPipelineVertexInputStateCreateInfo {
 				s_type: VkStructureType::PipelineVertexInputStateCreateInfo,
 				next: null(),
 				flags: 0,
-				vertex_binding_description_count: nvbuffers,
-				vertex_binding_descriptions: [
-					// Vertices
-					VkVertexInputBindingDescription {
-						binding: 0,
-						stride: (mem::size_of::<f32>() * 4) as u32,
-						input_rate: VkVertexInputRate::Vertex,
-					},
-					// Texture Coordinates
-					VkVertexInputBindingDescription {
-						binding: 1,
-						stride: (mem::size_of::<f32>() * 4) as u32,
-						input_rate: VkVertexInputRate::Vertex,
-					},
-					// Color
-					VkVertexInputBindingDescription {
-						binding: 2,
-						stride: (mem::size_of::<f32>() * 4) as u32,
-						input_rate: VkVertexInputRate::Vertex,
-					},
-				].as_ptr(),
-				vertex_attribute_description_count: nvbuffers,
-				vertex_attribute_descriptions: [
-					VkVertexInputAttributeDescription {
-						location: 0,
-						binding: 0,
-						format: VkFormat::R32g32b32a32Sfloat,
-						offset: 0,
-					},
-					VkVertexInputAttributeDescription {
-						location: 1,
-						binding: 1,
-						format: VkFormat::R32g32b32a32Sfloat,
-						offset: 0,
-					},
-					VkVertexInputAttributeDescription {
-						location: 2,
-						binding: 2,
-						format: VkFormat::R32g32b32a32Sfloat,
-						offset: 0,
-					},
-				].as_ptr(),
+				vertex_binding_description_count: bindings.len() as u32,
+				vertex_binding_descriptions: bindings.as_ptr(),
+				vertex_attribute_description_count: attributes.len()
+					as u32,
+				vertex_attribute_descriptions: attributes.as_ptr(),
 			},
 			input_assembly_state: &VkPipelineInputAssemblyStateCreateInfo {
 				s_type: VkStructureType::PipelineInputAssemblyStateCreateInfo,
 				next: null(),
 				flags: 0,
-				topology: VkPrimitiveTopology::TriangleFan,
-				primitive_restart_enable: 0,
+				topology: topology.vk(),
+				primitive_restart_enable: if primitive_restart { 1 }
+					else { 0 },
+			},
+			tessellation_state: if builder.tessellation.is_some() {
+				&tessellation_state
+			} else {
+				null()
 			},
-			tessellation_state: null(),
 			viewport_state: &VkPipelineViewportStateCreateInfo {
 				s_type: VkStructureType::PipelineViewportStateCreateInfo,
 				next: null(),
 				flags: 0,
-				viewport_count: 1,
+				viewport_count: nviewports,
 				viewports: null(),
-				scissor_count: 1,
+				scissor_count: nviewports,
 				scissors: null(),
 			},
 			rasterization_state: &VkPipelineRasterizationStateCreateInfo {
 				s_type: VkStructureType::PipelineRasterizationStateCreateInfo,
 				next: null(),
 				flags: 0,
-				depth_clamp_enable: 0,
+				depth_clamp_enable: depth_clamp as VkBool32,
 				rasterizer_discard_enable: 0,
-				polygon_mode: VkPolygonMode::Fill,
-				cull_mode: VkCullMode::Back,
-				front_face: VkFrontFace::CounterClockwise,
-				depth_bias_enable: 0,
-				depth_bias_constant_factor: 0.0,
-				depth_bias_clamp: 0.0,
-				depth_bias_slope_factor: 0.0,
-				line_width: 1.0,
+				polygon_mode: polygon_mode.vk(),
+				cull_mode: cull_mode.vk(),
+				front_face: front_face.vk(),
+				depth_bias_enable: depth_bias_enable as VkBool32,
+				depth_bias_constant_factor: depth_bias.constant,
+				depth_bias_clamp: depth_bias.clamp,
+				depth_bias_slope_factor: depth_bias.slope,
+				line_width,
 			},
 			multisample_state: &VkPipelineMultisampleStateCreateInfo {
 				s_type: VkStructureType::PipelineMultisampleStateCreateInfo,
 				next: null(),
 				flags: 0,
-				rasterization_samples: VK_SAMPLE_COUNT,
+				rasterization_samples: connection.sample_count,
 				sample_shading_enable: 0,
 				min_sample_shading: 0.0,
 				sample_mask: null(),
@@ -263,9 +837,9 @@ pub fn new_pipeline(vulkan: &mut Gpu, render_pass: VkRenderPass,
 				s_type: VkStructureType::PipelineDepthStencilStateCreateInfo,
 				next: null(),
 				flags: 0,
-				depth_test_enable: 1,
-				depth_write_enable: 1,
-				depth_compare_op: VkCompareOp::LessOrEqual,
+				depth_test_enable: depth_test as VkBool32,
+				depth_write_enable: depth_write as VkBool32,
+				depth_compare_op: depth_compare.vk(),
 				depth_bounds_test_enable: 0, // 
 				stencil_test_enable: 0,
 				front: NO_OP_STENCIL_STATE,
@@ -277,31 +851,18 @@ pub fn new_pipeline(vulkan: &mut Gpu, render_pass: VkRenderPass,
 				s_type: VkStructureType::PipelineColorBlendStateCreateInfo,
 				next: null(),
 				flags: 0,
-				logic_op_enable: 0,
-				logic_op: VkLogicOp::Clear,
-				attachment_count: 1,
-				attachments: &VkPipelineColorBlendAttachmentState {
-					blend_enable: if alpha { 1 } else { 0 },
-					src_color_blend_factor: VkBlendFactor::SrcAlpha,
-					dst_color_blend_factor: VkBlendFactor::OneMinusSrcAlpha,
-					color_blend_op: VkBlendOp::Add,
-					src_alpha_blend_factor: VkBlendFactor::SrcAlpha,
-					dst_alpha_blend_factor: VkBlendFactor::One,
-					alpha_blend_op: VkBlendOp::Add,
-					color_write_mask:
-						if alpha { 0b1111 } // RGBA
-						else { 0b111 }, // RGB
-				},
-				blend_constants: [0.0, 0.0, 0.0, 0.0],
+				logic_op_enable: logic_op.is_some() as VkBool32,
+				logic_op: logic_op.unwrap_or(LogicOp::Copy).vk(),
+				attachment_count: attachments.len() as u32,
+				attachments: attachments.as_ptr(),
+				blend_constants,
 			},
 			dynamic_state: &VkPipelineDynamicStateCreateInfo {
 				s_type: VkStructureType::PipelineDynamicStateCreateInfo,
 				next: null(),
 				flags: 0,
-				dynamic_state_count: 2,
-				dynamic_states: [
-					VkDynamicState::Viewport, VkDynamicState::Scissor
-				].as_ptr(),
+				dynamic_state_count: dynamic_states.len() as u32,
+				dynamic_states: dynamic_states.as_ptr(),
 			},
 			layout: pipeline_layout,
 			render_pass: render_pass,
@@ -314,7 +875,10 @@ pub fn new_pipeline(vulkan: &mut Gpu, render_pass: VkRenderPass,
 	).unwrap();
 
 	Style(Rc::new(StyleContext {
-		pipeline, pipeline_layout, descsetlayout, vulkan: vulkan.clone()
+		pipeline, pipeline_layout, descsetlayout, instance_binding,
+		descriptors0: descriptors0.clone(),
+		descriptors: descriptors.clone(), descsetlayout1,
+		vulkan: vulkan.clone()
 	}))
 }}
 
@@ -328,6 +892,10 @@ impl Drop for StyleContext {
 				self.pipeline_layout, null());
 			(vk.drop_descset_layout)(vk.device, self.descsetlayout,
 				null());
+			if let Some(layout) = self.descsetlayout1 {
+				(vk.drop_descset_layout)(vk.device, layout,
+					null());
+			}
 		}
 	}
 }
@@ -25,6 +25,8 @@ pub(crate) type VkDescriptorSetLayout = u64;
 pub(crate) type VkPipelineLayout = u64;
 pub type VkImageView = u64;
 pub type VkBuffer = u64;
+pub type VkBufferView = u64;
+pub type VkQueryPool = u64;
 pub(crate) type VkFence = u64;
 pub type VkSwapchainKHR = u64;
 pub type VkSemaphore = u64;
@@ -112,6 +114,15 @@ pub(crate) type VkCommandBuffer = *mut c_void;
 	pub extent: VkExtent3D,
 }
 
+#[repr(C)] pub(crate) struct VkBufferImageCopy {
+	pub buffer_offset: VkDeviceSize,
+	pub buffer_row_length: u32,
+	pub buffer_image_height: u32,
+	pub image_subresource: VkImageSubresourceLayers,
+	pub image_offset: VkOffset3D,
+	pub image_extent: VkExtent3D,
+}
+
 #[repr(C)] pub(crate) struct VkSemaphoreCreateInfo {
 	pub s_type: VkStructureType,
 	pub next: *const c_void,
@@ -119,7 +130,7 @@ pub(crate) type VkCommandBuffer = *mut c_void;
 }
 
 #[repr(C)] pub(crate) struct VkPushConstantRange {
-	pub stage_flags: VkShaderStage,
+	pub stage_flags: VkFlags,
 	pub offset: u32,
 	pub size: u32,
 }
@@ -167,6 +178,16 @@ pub(crate) type VkCommandBuffer = *mut c_void;
 	pub push_constant_ranges: *const VkPushConstantRange,
 }
 
+#[repr(C)] pub(crate) struct VkComputePipelineCreateInfo {
+	pub s_type: VkStructureType,
+	pub next: *const c_void,
+	pub flags: VkFlags,
+	pub stage: VkPipelineShaderStageCreateInfo,
+	pub layout: VkPipelineLayout,
+	pub base_pipeline_handle: VkPipeline,
+	pub base_pipeline_index: i32,
+}
+
 #[repr(C)] pub(crate) struct VkGraphicsPipelineCreateInfo {
 	pub s_type: VkStructureType,
 	pub next: *const c_void,
@@ -196,7 +217,20 @@ pub(crate) type VkCommandBuffer = *mut c_void;
 	pub stage: VkShaderStage,
 	pub module: VkShaderModule,
 	pub name: *const u8,
-	pub specialization_info: *const c_void,
+	pub specialization_info: *const VkSpecializationInfo,
+}
+
+#[repr(C)] #[derive(Copy, Clone)] pub(crate) struct VkSpecializationMapEntry {
+	pub constant_id: u32,
+	pub offset: u32,
+	pub size: usize,
+}
+
+#[repr(C)] pub(crate) struct VkSpecializationInfo {
+	pub map_entry_count: u32,
+	pub map_entries: *const VkSpecializationMapEntry,
+	pub data_size: usize,
+	pub data: *const c_void,
 }
 
 #[repr(C)] pub(crate) struct VkPipelineVertexInputStateCreateInfo {
@@ -240,7 +274,7 @@ pub(crate) type VkCommandBuffer = *mut c_void;
 	pub scissors: *const VkRect2D
 }
 
-#[repr(C)] pub(crate) struct VkViewport {
+#[derive(Copy, Clone)] #[repr(C)] pub(crate) struct VkViewport {
 	pub x: f32,
 	pub y: f32,
 	pub width: f32,
@@ -249,12 +283,12 @@ pub(crate) type VkCommandBuffer = *mut c_void;
 	pub max_depth: f32,
 }
 
-#[repr(C)] pub(crate) struct VkOffset2D {
+#[derive(Copy, Clone)] #[repr(C)] pub(crate) struct VkOffset2D {
 	pub x: i32,
 	pub y: i32,
 }
 
-#[repr(C)] pub(crate) struct VkRect2D {
+#[derive(Copy, Clone)] #[repr(C)] pub(crate) struct VkRect2D {
 	pub offset: VkOffset2D,
 	pub extent: VkExtent2D,
 }
@@ -337,7 +371,7 @@ pub(crate) type VkCommandBuffer = *mut c_void;
 	pub next: *const c_void,
 	pub flags: VkFlags,
 	pub code_size: usize,
-	pub code: *const u8, // Actually u32
+	pub code: *const u32,
 }
 
 #[repr(C)] pub(crate) struct VkDescriptorPoolCreateInfo {
@@ -373,6 +407,33 @@ pub(crate) type VkCommandBuffer = *mut c_void;
 	pub queue_family_indices: *const u32,
 }
 
+#[repr(C)] pub(crate) struct VkPipelineCacheCreateInfo {
+	pub s_type: VkStructureType,
+	pub next: *const c_void,
+	pub flags: VkFlags,
+	pub initial_data_size: usize,
+	pub initial_data: *const c_void,
+}
+
+#[repr(C)] pub(crate) struct VkQueryPoolCreateInfo {
+	pub s_type: VkStructureType,
+	pub next: *const c_void,
+	pub flags: VkFlags,
+	pub query_type: VkQueryType,
+	pub query_count: u32,
+	pub pipeline_statistics: VkFlags,
+}
+
+#[repr(C)] pub(crate) struct VkBufferViewCreateInfo {
+	pub s_type: VkStructureType,
+	pub next: *const c_void,
+	pub flags: VkFlags,
+	pub buffer: VkBuffer,
+	pub format: VkFormat,
+	pub offset: VkDeviceSize,
+	pub range: VkDeviceSize,
+}
+
 #[repr(C)] pub(crate) struct VkDescriptorBufferInfo {
 	pub buffer: VkBuffer,
 	pub offset: VkDeviceSize,
@@ -389,7 +450,7 @@ pub(crate) type VkCommandBuffer = *mut c_void;
 	pub descriptor_type: VkDescriptorType,
 	pub image_info: *const VkDescriptorImageInfo,
 	pub buffer_info: *const VkDescriptorBufferInfo,
-	pub texel_buffer_view: *const c_void,
+	pub texel_buffer_view: *const VkBufferView,
 }
 
 #[repr(C)] pub(crate) struct VkDescriptorImageInfo {
@@ -494,6 +555,193 @@ pub(crate) type VkCommandBuffer = *mut c_void;
 	pub heap_index: u32,
 }
 
+#[repr(C)] pub(crate) struct VkPhysicalDeviceLimits {
+	pub max_image_dimension_1d: u32,
+	pub max_image_dimension_2d: u32,
+	pub max_image_dimension_3d: u32,
+	pub max_image_dimension_cube: u32,
+	pub max_image_array_layers: u32,
+	pub max_texel_buffer_elements: u32,
+	pub max_uniform_buffer_range: u32,
+	pub max_storage_buffer_range: u32,
+	pub max_push_constants_size: u32,
+	pub max_memory_allocation_count: u32,
+	pub max_sampler_allocation_count: u32,
+	pub buffer_image_granularity: VkDeviceSize,
+	pub sparse_address_space_size: VkDeviceSize,
+	pub max_bound_descriptor_sets: u32,
+	pub max_per_stage_descriptor_samplers: u32,
+	pub max_per_stage_descriptor_uniform_buffers: u32,
+	pub max_per_stage_descriptor_storage_buffers: u32,
+	pub max_per_stage_descriptor_sampled_images: u32,
+	pub max_per_stage_descriptor_storage_images: u32,
+	pub max_per_stage_descriptor_input_attachments: u32,
+	pub max_per_stage_resources: u32,
+	pub max_descriptor_set_samplers: u32,
+	pub max_descriptor_set_uniform_buffers: u32,
+	pub max_descriptor_set_uniform_buffers_dynamic: u32,
+	pub max_descriptor_set_storage_buffers: u32,
+	pub max_descriptor_set_storage_buffers_dynamic: u32,
+	pub max_descriptor_set_sampled_images: u32,
+	pub max_descriptor_set_storage_images: u32,
+	pub max_descriptor_set_input_attachments: u32,
+	pub max_vertex_input_attributes: u32,
+	pub max_vertex_input_bindings: u32,
+	pub max_vertex_input_attribute_offset: u32,
+	pub max_vertex_input_binding_stride: u32,
+	pub max_vertex_output_components: u32,
+	pub max_tessellation_generation_level: u32,
+	pub max_tessellation_patch_size: u32,
+	pub max_tessellation_control_per_vertex_input_components: u32,
+	pub max_tessellation_control_per_vertex_output_components: u32,
+	pub max_tessellation_control_per_patch_output_components: u32,
+	pub max_tessellation_control_total_output_components: u32,
+	pub max_tessellation_evaluation_input_components: u32,
+	pub max_tessellation_evaluation_output_components: u32,
+	pub max_geometry_shader_invocations: u32,
+	pub max_geometry_input_components: u32,
+	pub max_geometry_output_components: u32,
+	pub max_geometry_output_vertices: u32,
+	pub max_geometry_total_output_components: u32,
+	pub max_fragment_input_components: u32,
+	pub max_fragment_output_attachments: u32,
+	pub max_fragment_dual_src_attachments: u32,
+	pub max_fragment_combined_output_resources: u32,
+	pub max_compute_shared_memory_size: u32,
+	pub max_compute_work_group_count: [u32; 3],
+	pub max_compute_work_group_invocations: u32,
+	pub max_compute_work_group_size: [u32; 3],
+	pub sub_pixel_precision_bits: u32,
+	pub sub_texel_precision_bits: u32,
+	pub mipmap_precision_bits: u32,
+	pub max_draw_indexed_index_value: u32,
+	pub max_draw_indirect_count: u32,
+	pub max_sampler_lod_bias: f32,
+	pub max_sampler_anisotropy: f32,
+	pub max_viewports: u32,
+	pub max_viewport_dimensions: [u32; 2],
+	pub viewport_bounds_range: [f32; 2],
+	pub viewport_sub_pixel_bits: u32,
+	pub min_memory_map_alignment: usize,
+	pub min_texel_buffer_offset_alignment: VkDeviceSize,
+	pub min_uniform_buffer_offset_alignment: VkDeviceSize,
+	pub min_storage_buffer_offset_alignment: VkDeviceSize,
+	pub min_texel_offset: i32,
+	pub max_texel_offset: u32,
+	pub min_texel_gather_offset: i32,
+	pub max_texel_gather_offset: u32,
+	pub min_interpolation_offset: f32,
+	pub max_interpolation_offset: f32,
+	pub sub_pixel_interpolation_offset_bits: u32,
+	pub max_framebuffer_width: u32,
+	pub max_framebuffer_height: u32,
+	pub max_framebuffer_layers: u32,
+	pub framebuffer_color_sample_counts: VkFlags,
+	pub framebuffer_depth_sample_counts: VkFlags,
+	pub framebuffer_stencil_sample_counts: VkFlags,
+	pub framebuffer_no_attachments_sample_counts: VkFlags,
+	pub max_color_attachments: u32,
+	pub sampled_image_color_sample_counts: VkFlags,
+	pub sampled_image_integer_sample_counts: VkFlags,
+	pub sampled_image_depth_sample_counts: VkFlags,
+	pub sampled_image_stencil_sample_counts: VkFlags,
+	pub storage_image_sample_counts: VkFlags,
+	pub max_sample_mask_words: u32,
+	pub timestamp_compute_and_graphics: VkBool32,
+	pub timestamp_period: f32,
+	pub max_clip_distances: u32,
+	pub max_cull_distances: u32,
+	pub max_combined_clip_and_cull_distances: u32,
+	pub discrete_queue_priorities: u32,
+	pub point_size_range: [f32; 2],
+	pub line_width_range: [f32; 2],
+	pub point_size_granularity: f32,
+	pub line_width_granularity: f32,
+	pub strict_lines: VkBool32,
+	pub standard_sample_locations: VkBool32,
+	pub optimal_buffer_copy_offset_alignment: VkDeviceSize,
+	pub optimal_buffer_copy_row_pitch_alignment: VkDeviceSize,
+	pub non_coherent_atom_size: VkDeviceSize,
+}
+
+#[derive(Copy, Clone)] #[repr(C)] pub(crate) struct VkPhysicalDeviceFeatures {
+	pub robust_buffer_access: VkBool32,
+	pub full_draw_index_uint32: VkBool32,
+	pub image_cube_array: VkBool32,
+	pub independent_blend: VkBool32,
+	pub geometry_shader: VkBool32,
+	pub tessellation_shader: VkBool32,
+	pub sample_rate_shading: VkBool32,
+	pub dual_src_blend: VkBool32,
+	pub logic_op: VkBool32,
+	pub multi_draw_indirect: VkBool32,
+	pub draw_indirect_first_instance: VkBool32,
+	pub depth_clamp: VkBool32,
+	pub depth_bias_clamp: VkBool32,
+	pub fill_mode_non_solid: VkBool32,
+	pub depth_bounds: VkBool32,
+	pub wide_lines: VkBool32,
+	pub large_points: VkBool32,
+	pub alpha_to_one: VkBool32,
+	pub multi_viewport: VkBool32,
+	pub sampler_anisotropy: VkBool32,
+	pub texture_compression_etc2: VkBool32,
+	pub texture_compression_astc_ldr: VkBool32,
+	pub texture_compression_bc: VkBool32,
+	pub occlusion_query_precise: VkBool32,
+	pub pipeline_statistics_query: VkBool32,
+	pub vertex_pipeline_stores_and_atomics: VkBool32,
+	pub fragment_stores_and_atomics: VkBool32,
+	pub shader_tessellation_and_geometry_point_size: VkBool32,
+	pub shader_image_gather_extended: VkBool32,
+	pub shader_storage_image_extended_formats: VkBool32,
+	pub shader_storage_image_multisample: VkBool32,
+	pub shader_storage_image_read_without_format: VkBool32,
+	pub shader_storage_image_write_without_format: VkBool32,
+	pub shader_uniform_buffer_array_dynamic_indexing: VkBool32,
+	pub shader_sampled_image_array_dynamic_indexing: VkBool32,
+	pub shader_storage_buffer_array_dynamic_indexing: VkBool32,
+	pub shader_storage_image_array_dynamic_indexing: VkBool32,
+	pub shader_clip_distance: VkBool32,
+	pub shader_cull_distance: VkBool32,
+	pub shader_float64: VkBool32,
+	pub shader_int64: VkBool32,
+	pub shader_int16: VkBool32,
+	pub shader_resource_residency: VkBool32,
+	pub shader_resource_min_lod: VkBool32,
+	pub sparse_binding: VkBool32,
+	pub sparse_residency_buffer: VkBool32,
+	pub sparse_residency_image2d: VkBool32,
+	pub sparse_residency_image3d: VkBool32,
+	pub sparse_residency2_samples: VkBool32,
+	pub sparse_residency4_samples: VkBool32,
+	pub sparse_residency8_samples: VkBool32,
+	pub sparse_residency16_samples: VkBool32,
+	pub sparse_residency_aliased: VkBool32,
+	pub variable_multisample_rate: VkBool32,
+	pub inherited_queries: VkBool32,
+}
+
+#[repr(C)] pub(crate) struct VkPhysicalDeviceSparseProperties {
+	pub residency_standard_2d_block_shape: VkBool32,
+	pub residency_standard_2d_multisample_block_shape: VkBool32,
+	pub residency_standard_3d_block_shape: VkBool32,
+	pub residency_aligned_mip_size: VkBool32,
+	pub residency_non_resident_strict: VkBool32,
+}
+
+#[repr(C)] pub(crate) struct VkPhysicalDeviceProperties {
+	pub api_version: u32,
+	pub driver_version: u32,
+	pub vendor_id: u32,
+	pub device_id: u32,
+	pub device_type: u32,
+	pub device_name: [i8; 256],
+	pub pipeline_cache_uuid: [u8; 16],
+	pub limits: VkPhysicalDeviceLimits,
+	pub sparse_properties: VkPhysicalDeviceSparseProperties,
+}
+
 #[repr(C)] pub(crate) struct VkPhysicalDeviceMemoryProperties {
 	pub memory_type_count: u32,
 	pub memory_types: [VkMemoryType; 32],
@@ -562,6 +810,26 @@ pub(crate) type VkCommandBuffer = *mut c_void;
 	pub flags: VkFlags,
 }
 
+#[repr(C)] #[allow(dead_code)] pub(crate) enum VkCommandBufferLevel {
+	Primary = 0,
+	Secondary = 1,
+}
+
+#[repr(C)] pub(crate) struct VkCommandPoolCreateInfo {
+	pub s_type: VkStructureType,
+	pub p_next: *mut c_void,
+	pub flags: u32,
+	pub queue_family_index: u32,
+}
+
+#[repr(C)] pub(crate) struct VkCommandBufferAllocateInfo {
+	pub s_type: VkStructureType,
+	pub p_next: *mut c_void,
+	pub command_pool: u64,
+	pub level: VkCommandBufferLevel,
+	pub command_buffer_count: u32,
+}
+
 #[repr(C)] pub(crate) struct VkCommandBufferBeginInfo {
 	pub s_type: VkStructureType,
 	pub p_next: *const c_void,
@@ -702,6 +970,18 @@ pub(crate) union VkClearValue {
 	pub depth_stencil: VkClearDepthStencilValue,
 }
 
+#[repr(C)] pub(crate) struct VkClearAttachment {
+	pub aspect_mask: VkImageAspectFlags,
+	pub color_attachment: u32,
+	pub clear_value: VkClearValue,
+}
+
+#[repr(C)] pub(crate) struct VkClearRect {
+	pub rect: VkRect2D,
+	pub base_array_layer: u32,
+	pub layer_count: u32,
+}
+
 #[repr(C)] #[allow(dead_code)] pub(crate) enum VkSubpassContents {
 	Inline = 0,
 	SecondaryCommandBuffers = 1,
@@ -750,7 +1030,7 @@ pub(crate) union VkClearValue {
 	DecrementAndWrap = 7,
 }
 
-#[repr(C)] #[allow(dead_code)] pub(crate) enum VkShaderStage {
+#[repr(C)] #[allow(dead_code)] #[derive(Copy, Clone)] pub(crate) enum VkShaderStage {
 	Vertex = 0x00000001,
 	TessellationControl = 0x00000002,
 	TessellationEvaluation = 0x00000004,
@@ -788,10 +1068,10 @@ pub(crate) union VkClearValue {
 }
 
 #[repr(C)] #[allow(dead_code)] pub(crate) enum VkCullMode {
-//	None = 0,
-//	Front = 0x00000001,
+	None = 0,
+	Front = 0x00000001,
 	Back = 0x00000002,
-//	FrontAndBack = 0x00000003,
+	FrontAndBack = 0x00000003,
 }
 
 #[repr(C)] #[allow(dead_code)] pub(crate) enum VkFrontFace {
@@ -882,6 +1162,7 @@ pub(crate) union VkClearValue {
 	VertexBufferBit = 0x00000080,
 	IndirectBufferBit = 0x00000100,
 	VertexIndexBufferBit = 0x00000040 | 0x00000080,
+	StorageVertexBufferBit = 0x00000020 | 0x00000080,
 }
 
 #[repr(C)] #[allow(dead_code)] pub(crate) enum VkDescriptorType {
@@ -898,6 +1179,19 @@ pub(crate) union VkClearValue {
 	InputAttachment = 10,
 } 
 
+#[repr(C)] #[allow(dead_code)] pub(crate) enum VkQueryType {
+	Occlusion = 0,
+	PipelineStatistics = 1,
+	Timestamp = 2,
+}
+
+#[repr(C)] #[allow(dead_code)] pub(crate) enum VkQueryResult {
+	Result64Bit = 0x00000001,
+	Wait = 0x00000002,
+	WithAvailability = 0x00000004,
+	Partial = 0x00000008,
+}
+
 #[repr(C)] #[allow(dead_code)] pub(crate) enum VkPipelineBindPoint {
 	Graphics = 0,
 	Compute = 1,
@@ -919,7 +1213,7 @@ pub(crate) union VkClearValue {
 	Linear = 1,
 }
 
-#[repr(C)] #[allow(dead_code)] pub enum VkSampleCount {
+#[repr(C)] #[allow(dead_code)] #[derive(Copy, Clone)] pub enum VkSampleCount {
 	Sc1 = 0x00000001,
 	Sc2 = 0x00000002,
 	Sc4 = 0x00000004,
@@ -959,7 +1253,7 @@ pub(crate) union VkClearValue {
 	ColorAttachmentReadWrite = 0x00000080 | 0x00000100,
 }
 
-#[repr(C)] #[allow(dead_code)] pub enum VkImageLayout {
+#[repr(C)] #[allow(dead_code)] #[derive(Copy, Clone)] pub enum VkImageLayout {
 	Undefined = 0,
 	General = 1,
 	ColorAttachmentOptimal = 2,
@@ -992,12 +1286,15 @@ pub(crate) union VkClearValue {
 	AllCommands = 0x00010000,
 	TopOfPipeAndColorAttachmentOutput = 0x00000401,
 	TopOfPipeAndEarlyFragmentTests = 0x00000101,
+	DrawIndirectAndVertexInput = 0x00000002 | 0x00000004,
+	VertexShaderAndFragmentShader = 0x00000008 | 0x00000080,
 }
 
 #[repr(C)] #[allow(dead_code)] pub(crate) enum VkCommandBufferUsage {
 	OneTimeSubmitBit = 0x00000001,
 	RenderPassContinueBit = 0x00000002,
 	SimultaneousUseBit = 0x00000004,
+	OneTimeSubmitRenderPassContinue = 0x00000001 | 0x00000002,
 }
 
 #[repr(C)] #[allow(dead_code)] pub(crate) enum VkImageAspectFlags {
@@ -1043,6 +1340,10 @@ pub(crate) union VkClearValue {
 	InputAttachmentBit = 0x00000080,
 	TransferDstAndUsage = 0x00000006,
 	TransientColorAttachment = 0x00000040 | 0x00000010,
+	ColorAttachmentTransferSrc = 0x00000010 | 0x00000001,
+	ColorAttachmentSampledTransferSrc = 0x00000010 | 0x00000004
+		| 0x00000001,
+	StorageSampled = 0x00000008 | 0x00000004,
 }
 
 #[repr(C)] #[allow(dead_code)] #[derive(PartialEq, Clone)]
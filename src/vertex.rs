@@ -0,0 +1,276 @@
+// "asi_vulkan" - Aldaron's System Interface - Vulkan
+//
+// Copyright Jeron A. Lau 2018.
+// Distributed under the Boost Software License, Version 1.0.
+// (See accompanying file LICENSE_1_0.txt or copy at
+// https://www.boost.org/LICENSE_1_0.txt)
+
+use types::*;
+use std::mem;
+
+/// The format of a vertex attribute, as read by the vertex shader.
+#[derive(Copy, Clone, Debug, PartialEq)] pub enum VertexFormat {
+	/// `f32` as `float`.
+	Float,
+	/// `[f32; 2]` as `vec2`.
+	Vec2,
+	/// `[f32; 3]` as `vec3`.
+	Vec3,
+	/// `[f32; 4]` as `vec4`.
+	Vec4,
+	/// 2 half floats (`[u16; 2]`) as `vec2`.
+	Half2,
+	/// 4 half floats (`[u16; 4]`) as `vec4`.
+	Half4,
+	/// `[u8; 4]` as `vec4` from 0.0 to 1.0, for colors.
+	Unorm8x4,
+	/// `[i8; 4]` as `vec4` from -1.0 to 1.0, for packed normals.
+	Snorm8x4,
+	/// `[u16; 4]` as `vec4` from 0.0 to 1.0.
+	Unorm16x4,
+	/// `[u8; 4]` as `uvec4`, for bone indices.
+	Uint8x4,
+	/// `u32` as `uint`.
+	Uint,
+	/// `i32` as `int`.
+	Int,
+	/// `[u32; 4]` as `uvec4`.
+	Uvec4,
+	/// `[i32; 4]` as `ivec4`.
+	Ivec4,
+}
+
+impl VertexFormat {
+	/// The size of the attribute in bytes.
+	pub fn size(&self) -> u32 {
+		use self::VertexFormat::*;
+
+		match *self {
+			Float | Half2 | Unorm8x4 | Snorm8x4 | Uint8x4 | Uint
+				| Int => 4,
+			Vec2 | Half4 | Unorm16x4 => 8,
+			Vec3 => 12,
+			Vec4 | Uvec4 | Ivec4 => 16,
+		}
+	}
+
+	pub(crate) fn vk(&self) -> VkFormat {
+		use self::VertexFormat::*;
+
+		match *self {
+			Float => VkFormat::R32Sfloat,
+			Vec2 => VkFormat::R32g32Sfloat,
+			Vec3 => VkFormat::R32g32b32Sfloat,
+			Vec4 => VkFormat::R32g32b32a32Sfloat,
+			Half2 => VkFormat::R16g16Sfloat,
+			Half4 => VkFormat::R16g16b16a16Sfloat,
+			Unorm8x4 => VkFormat::R8g8b8a8Unorm,
+			Snorm8x4 => VkFormat::R8g8b8a8Snorm,
+			Unorm16x4 => VkFormat::R16g16b16a16Unorm,
+			Uint8x4 => VkFormat::R8g8b8a8Uint,
+			Uint => VkFormat::R32Uint,
+			Int => VkFormat::R32Sint,
+			Uvec4 => VkFormat::R32g32b32a32Uint,
+			Ivec4 => VkFormat::R32g32b32a32Sint,
+		}
+	}
+}
+
+/// Whether a vertex buffer binding advances per vertex or per instance.
+#[derive(Copy, Clone, Debug, PartialEq)] pub enum InputRate {
+	Vertex,
+	Instance,
+}
+
+/// A vertex struct that can be read from a vertex buffer.  Implement it with
+/// the `vertex!` macro.
+pub trait Vertex: Copy {
+	/// The format and byte offset of each field, in location order.
+	fn attributes() -> Vec<(VertexFormat, u32)>;
+}
+
+/// Implement `Vertex` for a `#[repr(C)]` struct, listing its fields in
+/// location order with their `VertexFormat`s:
+///
+/// ```ignore
+/// #[repr(C)] #[derive(Copy, Clone)] struct MeshVertex {
+///     position: [f32; 3],
+///     normal: [f32; 3],
+///     uv: [f32; 2],
+/// }
+///
+/// vertex!(MeshVertex { position: Vec3, normal: Vec3, uv: Vec2 });
+/// ```
+#[macro_export]
+macro_rules! vertex {
+	($vertex:ty { $($field:ident: $format:ident),* $(,)* }) => {
+		impl $crate::Vertex for $vertex {
+			fn attributes() -> Vec<($crate::VertexFormat, u32)> {
+				// Only field addresses are taken, without creating
+				// references to the uninitialized vertex.
+				let vertex = ::std::mem::MaybeUninit::<$vertex>
+					::uninit();
+				let base = vertex.as_ptr();
+
+				vec![$(
+					($crate::VertexFormat::$format, unsafe {
+						(::std::ptr::addr_of!((*base).$field)
+							as *const u8)
+							.offset_from(base as *const u8)
+					} as u32)
+				),*]
+			}
+		}
+	}
+}
+
+/// One vertex buffer binding of a `VertexLayout`.
+#[derive(Clone, Debug, PartialEq)] struct Binding {
+	stride: u32,
+	rate: InputRate,
+	attributes: Vec<(VertexFormat, u32)>,
+}
+
+/// How vertex buffers are read into vertex shader inputs.  Bindings are
+/// numbered in the order they're added, and attribute locations continue
+/// from one binding to the next.
+#[derive(Clone, Debug, PartialEq, Default)] pub struct VertexLayout {
+	bindings: Vec<Binding>,
+}
+
+impl VertexLayout {
+	/// Create a layout with no vertex buffers.
+	pub fn new() -> Self {
+		VertexLayout { bindings: vec![] }
+	}
+
+	/// Add a binding that reads `stride` bytes per vertex or instance,
+	/// with an attribute for each format and byte offset in `attributes`.
+	pub fn binding(mut self, stride: u32, rate: InputRate,
+		attributes: &[(VertexFormat, u32)]) -> Self
+	{
+		self.bindings.push(Binding {
+			stride, rate, attributes: attributes.to_vec(),
+		});
+		self
+	}
+
+	/// Add one per-vertex binding with `formats` tightly packed in order.
+	pub fn interleaved(self, formats: &[VertexFormat]) -> Self {
+		let mut offset = 0;
+		let attributes: Vec<(VertexFormat, u32)> = formats.iter()
+			.map(|format| {
+				offset += format.size();
+				(*format, offset - format.size())
+			}).collect();
+
+		self.binding(offset, InputRate::Vertex, &attributes)
+	}
+
+	/// Add a separate per-vertex binding for each of `formats`.
+	pub fn split(self, formats: &[VertexFormat]) -> Self {
+		formats.iter().fold(self, |layout, format| {
+			layout.binding(format.size(), InputRate::Vertex,
+				&[(*format, 0)])
+		})
+	}
+
+	/// Add a per-vertex binding of `V`s.
+	pub fn vertex<V: Vertex>(self) -> Self {
+		self.binding(mem::size_of::<V>() as u32, InputRate::Vertex,
+			&V::attributes())
+	}
+
+	/// Add a per-instance binding of `V`s.
+	pub fn instance<V: Vertex>(self) -> Self {
+		self.binding(mem::size_of::<V>() as u32, InputRate::Instance,
+			&V::attributes())
+	}
+
+	/// The layout `Style::new()` uses: one vec4 binding for each of
+	/// `nvbuffers` vertex buffers.
+	pub(crate) fn vec4s(nvbuffers: u32) -> Self {
+		VertexLayout::new()
+			.split(&vec![VertexFormat::Vec4; nvbuffers as usize])
+	}
+
+	pub(crate) fn nbindings(&self) -> u32 {
+		self.bindings.len() as u32
+	}
+
+	pub(crate) fn nattributes(&self) -> u32 {
+		self.bindings.iter().map(|b| b.attributes.len() as u32).sum()
+	}
+
+	pub(crate) fn max_stride(&self) -> u32 {
+		self.bindings.iter().map(|b| b.stride).max().unwrap_or(0)
+	}
+
+	pub(crate) fn max_offset(&self) -> u32 {
+		self.bindings.iter().flat_map(|b| b.attributes.iter())
+			.map(|&(_, offset)| offset).max().unwrap_or(0)
+	}
+
+	pub(crate) fn vk(&self) -> (Vec<VkVertexInputBindingDescription>,
+		Vec<VkVertexInputAttributeDescription>)
+	{
+		let mut bindings = vec![];
+		let mut attributes = vec![];
+
+		for (i, binding) in self.bindings.iter().enumerate() {
+			bindings.push(VkVertexInputBindingDescription {
+				binding: i as u32,
+				stride: binding.stride,
+				input_rate: match binding.rate {
+					InputRate::Vertex =>
+						VkVertexInputRate::Vertex,
+					InputRate::Instance =>
+						VkVertexInputRate::Instance,
+				},
+			});
+
+			for &(format, offset) in binding.attributes.iter() {
+				attributes.push(VkVertexInputAttributeDescription {
+					location: attributes.len() as u32,
+					binding: i as u32,
+					format: format.vk(),
+					offset,
+				});
+			}
+		}
+
+		(bindings, attributes)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[repr(C)] #[derive(Copy, Clone)] struct MeshVertex {
+		position: [f32; 3],
+		normal: [f32; 3],
+		uv: [f32; 2],
+	}
+
+	vertex!(MeshVertex { position: Vec3, normal: Vec3, uv: Vec2 });
+
+	#[test]
+	fn attributes() {
+		assert_eq!(MeshVertex::attributes(), vec![
+			(VertexFormat::Vec3, 0),
+			(VertexFormat::Vec3, 12),
+			(VertexFormat::Vec2, 24),
+		]);
+	}
+
+	#[test]
+	fn layout() {
+		let layout = VertexLayout::new().vertex::<MeshVertex>();
+
+		assert_eq!(layout, VertexLayout::new().interleaved(&[
+			VertexFormat::Vec3, VertexFormat::Vec3,
+			VertexFormat::Vec2,
+		]));
+	}
+}
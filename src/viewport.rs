@@ -0,0 +1,70 @@
+// "asi_vulkan" - Aldaron's System Interface - Vulkan
+//
+// Copyright Jeron A. Lau 2018.
+// Distributed under the Boost Software License, Version 1.0.
+// (See accompanying file LICENSE_1_0.txt or copy at
+// https://www.boost.org/LICENSE_1_0.txt)
+
+use types::*;
+
+/// A rectangle of the framebuffer to draw into, in pixels from the top left,
+/// and the range of depth values to map to.
+#[derive(Copy, Clone, Debug, PartialEq)] pub struct Viewport {
+	pub x: f32,
+	pub y: f32,
+	pub width: f32,
+	pub height: f32,
+	/// Depth that 0.0 in normalized device coordinates maps to.
+	pub min_depth: f32,
+	/// Depth that 1.0 in normalized device coordinates maps to.
+	pub max_depth: f32,
+}
+
+impl Viewport {
+	/// Create a viewport with the full 0.0 to 1.0 depth range.
+	pub fn new(x: f32, y: f32, width: f32, height: f32) -> Self {
+		Viewport { x, y, width, height, min_depth: 0.0, max_depth: 1.0 }
+	}
+
+	/// Set the depth range (for example, 0.0 to 0.1 to draw a HUD in front
+	/// of everything else).
+	pub fn depth(self, min_depth: f32, max_depth: f32) -> Self {
+		Viewport { min_depth, max_depth, ..self }
+	}
+
+	pub(crate) fn vk(&self) -> VkViewport {
+		VkViewport {
+			x: self.x, y: self.y,
+			width: self.width,
+			height: self.height,
+			min_depth: self.min_depth,
+			max_depth: self.max_depth,
+		}
+	}
+}
+
+/// A rectangle of the framebuffer outside of which nothing is drawn, in pixels
+/// from the top left.
+#[derive(Copy, Clone, Debug, PartialEq)] pub struct Scissor {
+	pub x: i32,
+	pub y: i32,
+	pub width: u32,
+	pub height: u32,
+}
+
+impl Scissor {
+	/// Create a scissor rectangle.
+	pub fn new(x: i32, y: i32, width: u32, height: u32) -> Self {
+		Scissor { x, y, width, height }
+	}
+
+	pub(crate) fn vk(&self) -> VkRect2D {
+		VkRect2D {
+			offset: VkOffset2D { x: self.x, y: self.y },
+			extent: VkExtent2D {
+				width: self.width,
+				height: self.height,
+			},
+		}
+	}
+}
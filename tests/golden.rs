@@ -0,0 +1,282 @@
+// "asi_vulkan" - Aldaron's System Interface - Vulkan
+//
+// Copyright Jeron A. Lau 2018.
+// Distributed under the Boost Software License, Version 1.0.
+// (See accompanying file LICENSE_1_0.txt or copy at
+// https://www.boost.org/LICENSE_1_0.txt)
+
+//! Golden-image regression tests.  Scenes are rendered offscreen (on a
+//! software implementation like lavapipe or SwiftShader in CI), read back
+//! and compared against the reference images in `tests/golden/`.
+//!
+//! On failure the rendered image and a diff image (mismatched pixels in
+//! red) are written to `target/golden/`.  Set `ASI_VULKAN_BLESS=1` to
+//! overwrite the reference images with what is rendered.  The tests need a
+//! Vulkan implementation, so they're ignored unless asked for (and fail if
+//! there isn't one).  To run them on lavapipe:
+//!
+//! `VK_ICD_FILENAMES=/usr/share/vulkan/icd.d/lvp_icd.x86_64.json cargo test
+//! -- --ignored`
+
+extern crate asi_vulkan;
+
+use asi_vulkan::*;
+use asi_vulkan::types::VkPipelineStage;
+use std::{ env, fs, fs::File, io::Read, path::PathBuf };
+
+const WIDTH: u32 = 64;
+const HEIGHT: u32 = 64;
+/// How far off each color channel of a pixel may be.
+const TOLERANCE: u8 = 2;
+
+// gl_Position = position (location 0).
+const VERTEX: &[u32] = &[
+	0x07230203, 0x00010000, 0x00000000, 0x0000000c,
+	0x00000000, 0x00020011, 0x00000001, 0x0003000e,
+	0x00000000, 0x00000001, 0x0007000f, 0x00000000,
+	0x00000001, 0x6e69616d, 0x00000000, 0x00000008,
+	0x00000009, 0x00040047, 0x00000008, 0x0000001e,
+	0x00000000, 0x00040047, 0x00000009, 0x0000000b,
+	0x00000000, 0x00020013, 0x00000002, 0x00030021,
+	0x00000003, 0x00000002, 0x00030016, 0x00000004,
+	0x00000020, 0x00040017, 0x00000005, 0x00000004,
+	0x00000004, 0x00040020, 0x00000006, 0x00000001,
+	0x00000005, 0x00040020, 0x00000007, 0x00000003,
+	0x00000005, 0x0004003b, 0x00000006, 0x00000008,
+	0x00000001, 0x0004003b, 0x00000007, 0x00000009,
+	0x00000003, 0x00050036, 0x00000002, 0x00000001,
+	0x00000000, 0x00000003, 0x000200f8, 0x0000000a,
+	0x0004003d, 0x00000005, 0x0000000b, 0x00000008,
+	0x0003003e, 0x00000009, 0x0000000b, 0x000100fd,
+	0x00010038,
+];
+
+// color = vec4(1.0, 0.0, 0.0, 1.0).
+const FRAGMENT: &[u32] = &[
+	0x07230203, 0x00010000, 0x00000000, 0x0000000c,
+	0x00000000, 0x00020011, 0x00000001, 0x0003000e,
+	0x00000000, 0x00000001, 0x0006000f, 0x00000004,
+	0x00000001, 0x6e69616d, 0x00000000, 0x00000007,
+	0x00030010, 0x00000001, 0x00000007, 0x00040047,
+	0x00000007, 0x0000001e, 0x00000000, 0x00020013,
+	0x00000002, 0x00030021, 0x00000003, 0x00000002,
+	0x00030016, 0x00000004, 0x00000020, 0x00040017,
+	0x00000005, 0x00000004, 0x00000004, 0x00040020,
+	0x00000006, 0x00000003, 0x00000005, 0x0004002b,
+	0x00000004, 0x00000008, 0x00000000, 0x0004002b,
+	0x00000004, 0x00000009, 0x3f800000, 0x0007002c,
+	0x00000005, 0x0000000a, 0x00000009, 0x00000008,
+	0x00000008, 0x00000009, 0x0004003b, 0x00000006,
+	0x00000007, 0x00000003, 0x00050036, 0x00000002,
+	0x00000001, 0x00000000, 0x00000003, 0x000200f8,
+	0x0000000b, 0x0003003e, 0x00000007, 0x0000000a,
+	0x000100fd, 0x00010038,
+];
+
+fn gpu() -> Gpu {
+	Gpu::new_headless(Vec3::new(0.0, 0.0, 1.0))
+		.unwrap_or_else(|e| panic!("No Vulkan: {}", e))
+}
+
+fn spirv(words: &[u32]) -> Vec<u8> {
+	let mut bytes = Vec::with_capacity(words.len() * 4);
+
+	for word in words {
+		bytes.extend_from_slice(&[*word as u8, (*word >> 8) as u8,
+			(*word >> 16) as u8, (*word >> 24) as u8]);
+	}
+
+	bytes
+}
+
+/// Render one frame to `target` with `draw`, and read it back.
+fn render<F: FnOnce(&Gpu)>(gpu: &Gpu, target: &RenderTarget, draw: F)
+	-> Vec<u8>
+{ unsafe {
+	let fence = Fence::new(gpu);
+
+	draw_begin_target(gpu, target);
+	draw(gpu);
+	end_render_pass(gpu);
+	end_cmdbuff(gpu);
+	queue_submit(gpu, &fence, VkPipelineStage::ColorAttachmentOutput,
+		None);
+	wait_fence(gpu, &fence);
+
+	readback_target(gpu, target, 0)
+} }
+
+fn path(dir: &str, file: String) -> PathBuf {
+	let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+	path.push(dir);
+	path.push(file);
+	path
+}
+
+/// Read a binary PPM file as tightly packed RGB8.
+fn read_ppm(path: &PathBuf) -> Option<(u32, u32, Vec<u8>)> {
+	let mut data = vec![];
+	File::open(path).ok()?.read_to_end(&mut data).ok()?;
+
+	// Header: magic, width, height & max value, separated by whitespace.
+	let mut fields = vec![];
+	let mut start = 0;
+	let mut i = 0;
+	while fields.len() < 4 {
+		if data.get(i)?.is_ascii_whitespace() {
+			if i > start {
+				fields.push(String::from_utf8(
+					data[start..i].to_vec()).ok()?);
+			}
+			start = i + 1;
+		}
+		i += 1;
+	}
+
+	if fields[0] != "P6" || fields[3] != "255" {
+		return None;
+	}
+
+	let width = fields[1].parse().ok()?;
+	let height = fields[2].parse().ok()?;
+
+	Some((width, height, data[start..].to_vec()))
+}
+
+/// Compare `rgba` against reference image `name`.
+fn check(name: &str, rgba: &[u8]) {
+	let reference = path("tests/golden", format!("{}.ppm", name));
+
+	if env::var("ASI_VULKAN_BLESS").is_ok() {
+		write_ppm(&reference, WIDTH, HEIGHT, rgba).unwrap();
+		return;
+	}
+
+	let (width, height, rgb) = read_ppm(&reference).expect(
+		"Missing reference image, run with ASI_VULKAN_BLESS=1");
+
+	assert_eq!((width, height), (WIDTH, HEIGHT));
+
+	let mut diff = Vec::with_capacity(rgba.len());
+	let mut mismatches = 0;
+
+	for (actual, expected) in rgba.chunks(4).zip(rgb.chunks(3)) {
+		let off = (0..3).any(|c| {
+			(actual[c] as i16 - expected[c] as i16).abs()
+				> TOLERANCE as i16
+		});
+
+		if off {
+			mismatches += 1;
+			diff.extend_from_slice(&[255, 0, 0, 255]);
+		} else {
+			// Dimmed grayscale of the expected pixel.
+			let gray = ((expected[0] as u16 + expected[1] as u16
+				+ expected[2] as u16) / 12) as u8;
+			diff.extend_from_slice(&[gray, gray, gray, 255]);
+		}
+	}
+
+	if mismatches != 0 {
+		let out = path("target", "golden".to_string());
+		fs::create_dir_all(&out).unwrap();
+
+		write_png(out.join(format!("{}.png", name)), WIDTH, HEIGHT,
+			rgba).unwrap();
+		write_png(out.join(format!("{}-diff.png", name)), WIDTH,
+			HEIGHT, &diff).unwrap();
+
+		panic!("{}: {} pixels differ from the reference, see {:?}",
+			name, mismatches, out);
+	}
+}
+
+#[test] #[ignore = "needs a Vulkan implementation"]
+fn clear() {
+	let gpu = gpu();
+	let target = RenderTarget::new(&gpu, WIDTH, HEIGHT, 1, true);
+
+	let rgba = render(&gpu, &target, |_| {});
+
+	check("clear", &rgba);
+}
+
+#[test] #[ignore = "needs a Vulkan implementation"]
+fn sprite() {
+	let mut gpu = gpu();
+	let target = RenderTarget::new(&gpu, WIDTH, HEIGHT, 1, true);
+
+	let vertex = ShaderModule::new(&gpu, &spirv(VERTEX));
+	let fragment = ShaderModule::new(&gpu, &spirv(FRAGMENT));
+	let style = Style::new(&mut gpu, target.render_pass(), &vertex,
+		&fragment, 0, 1, false);
+
+	unsafe {
+		let (camera, effect) = vw_camera_new(&gpu,
+			(0.0, 0.0, 0.0, 1.0), (0.0, 1.0));
+		let sprite = Sprite::new(&gpu, &style,
+			TransformUniform { mat4: [0.0; 16] }, &camera,
+			Some(&effect), None, false);
+
+		// Left half of the target.
+		let quad = new_buffer(&gpu, &[
+			-1.0, -1.0, 0.0, 1.0,
+			-1.0, 1.0, 0.0, 1.0,
+			0.0, 1.0, 0.0, 1.0,
+			0.0, -1.0, 0.0, 1.0,
+		]);
+
+		let rgba = render(&gpu, &target, |gpu| {
+			cmd_bind_pipeline(gpu, sprite.pipeline);
+			cmd_bind_descsets(gpu, sprite.pipeline_layout,
+				sprite.handles().0);
+			cmd_bind_vb(gpu, &[quad.buffer()]);
+			cmd_draw(gpu, 4, 1, 0, 0);
+		});
+
+		check("sprite", &rgba);
+	}
+}
+
+#[test] #[ignore = "needs a Vulkan implementation"]
+fn indexed() {
+	let mut gpu = gpu();
+	let target = RenderTarget::new(&gpu, WIDTH, HEIGHT, 1, true);
+
+	let vertex = ShaderModule::new(&gpu, &spirv(VERTEX));
+	let fragment = ShaderModule::new(&gpu, &spirv(FRAGMENT));
+	let style = StyleBuilder::new(target.render_pass(), &vertex,
+		&fragment, 0, 1).primitive_restart(true).build(&mut gpu);
+
+	unsafe {
+		let (camera, effect) = vw_camera_new(&gpu,
+			(0.0, 0.0, 0.0, 1.0), (0.0, 1.0));
+		let sprite = Sprite::new(&gpu, &style,
+			TransformUniform { mat4: [0.0; 16] }, &camera,
+			Some(&effect), None, false);
+
+		// Corners of the target, and the middle of the top and bottom.
+		let vertices = new_buffer(&gpu, &[
+			-1.0, -1.0, 0.0, 1.0,
+			-1.0, 1.0, 0.0, 1.0,
+			0.0, 1.0, 0.0, 1.0,
+			0.0, -1.0, 0.0, 1.0,
+			1.0, 1.0, 0.0, 1.0,
+			1.0, -1.0, 0.0, 1.0,
+		]);
+		// Right half of the target, as two fans split by a restart.
+		let indices = Buffer::new(&gpu, &[3u16, 2, 4, 0xFFFF, 3, 4, 5],
+			BufferBuilderType::Index);
+
+		let rgba = render(&gpu, &target, |gpu| {
+			cmd_bind_pipeline(gpu, sprite.pipeline);
+			cmd_bind_descsets(gpu, sprite.pipeline_layout,
+				sprite.handles().0);
+			cmd_bind_vb(gpu, &[vertices.buffer()]);
+			cmd_bind_ib(gpu, indices.buffer(), 0, IndexType::U16);
+			cmd_draw_indexed(gpu, 7, 1, 0, 0, 0);
+		});
+
+		check("indexed", &rgba);
+	}
+}